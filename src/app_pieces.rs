@@ -12,6 +12,8 @@ use crate::engine::*;
 use crate::game_board::*;
 use crate::evaluation::*;
 use crate::move_compute::*;
+use crate::skill::*;
+use crate::timer::*;
 
 pub struct BoardPlugin;
 
@@ -60,14 +62,25 @@ pub struct GameSettings{
     pub engine_color: bool,
     pub starting_pos : String,
     pub think_time: u32,
+    pub skill_level: u8,
+    pub contempt: i16,
 }
 
 impl GameSettings{
+    fn get_search_limits(&self) -> SearchLimits{
+        let mut limits = SearchLimits::with_skill(self.think_time, SkillLevel::new(self.skill_level));
+        limits.contempt = self.contempt;
+
+        return limits;
+    }
+
     fn new() -> Self{
         GameSettings{
             engine_color: false,
             starting_pos: DEFAULT_FEN.to_string(),
             think_time : DEFAULT_THINK_TIME as u32,
+            skill_level: MAX_SKILL_LEVEL,
+            contempt: DEFAULT_CONTEMPT,
         }
     }
 }
@@ -81,23 +94,28 @@ struct ChessPieceAssets{
 struct ReceiveMoveTag(Mutex<Receiver<u16>>);
 
 #[derive(Resource)]
-struct SendBoardTag(Mutex<Sender<(GameChessBoard, u32)>>);
+struct SendBoardTag(Mutex<Sender<(GameChessBoard, SearchLimits)>>);
 
 #[derive(Resource)]
 struct ReceiveMoveTag2(Mutex<Receiver<String>>);
 
+// signal of the ponder search waiting for the player's reply, if there is one
+#[derive(Resource)]
+struct PonderSignalTag(Arc<Mutex<Option<Arc<SearchSignal>>>>);
+
 
 // store the best move
 struct EngineMove(u16);
 
 impl Plugin for BoardPlugin{
     fn build(&self, app:&mut App){
-        let (rx, tx) = engine_move_handling();
+        let (rx, tx, ponder_signal) = engine_move_handling();
         let rx2 = engine_update_settings();
 
         app.insert_resource(ReceiveMoveTag(Mutex::new(rx)))
             .insert_resource(SendBoardTag(Mutex::new(tx)))
             .insert_resource(ReceiveMoveTag2(Mutex::new(rx2)))
+            .insert_resource(PonderSignalTag(ponder_signal))
             .insert_resource(create_game_settings())
 
             .add_systems(Startup, load_chess_piece_images.before(spawn_board_parent).before(spawn_board_pieces).before(spawn_piece_follow))
@@ -108,28 +126,88 @@ impl Plugin for BoardPlugin{
     }
 }
 
-fn engine_move_handling() -> (Receiver<u16>, Sender<(GameChessBoard, u32)>) {
+// a search running on the position we expect after the player's reply
+struct PonderSearch{
+    zobrist_hash: u64,
+    signal: Arc<SearchSignal>,
+    handle: thread::JoinHandle<MoveScorePair>,
+}
+
+fn engine_move_handling() -> (Receiver<u16>, Sender<(GameChessBoard, SearchLimits)>, Arc<Mutex<Option<Arc<SearchSignal>>>>) {
     let (tx, rx): (Sender<u16>, Receiver<u16>) = channel();
 
-    let (tx2, rx2): (Sender<(GameChessBoard, u32)>, Receiver<(GameChessBoard, u32)>) = channel();
+    let (tx2, rx2): (Sender<(GameChessBoard, SearchLimits)>, Receiver<(GameChessBoard, SearchLimits)>) = channel();
+
+    let ponder_signal: Arc<Mutex<Option<Arc<SearchSignal>>>> = Arc::new(Mutex::new(None));
+    let thread_ponder_signal = ponder_signal.clone();
 
     thread::spawn(move || {
+        let mut ponder_search: Option<PonderSearch> = None;
+
         loop {
             thread::sleep(time::Duration::from_millis(50));
-            if let Ok((mut game_chess_board, think_time)) = rx2.try_recv() {
+            if let Ok((mut game_chess_board, mut limits)) = rx2.try_recv() {
 
                 // if we changed when the bot is making a mv, we want to
 
-                let mv: MoveScorePair = get_best_move(&mut game_chess_board, think_time);
+                let mut pondered_mv: Option<MoveScorePair> = None;
+
+                if let Some(ponder) = ponder_search.take(){
+                    thread_ponder_signal.lock().unwrap().take();
+
+                    // the player made the move we expected so the ponder search just keeps going
+                    if ponder.zobrist_hash == game_chess_board.board.zobrist_hash{
+                        ponder.signal.ponderhit();
+                    }
+                    else{
+                        ponder.signal.stop();
+                    }
+
+                    let ponder_mv = ponder.handle.join().unwrap();
+
+                    if !ponder.signal.is_stopped() && ponder_mv.mv != 0{
+                        pondered_mv = Some(ponder_mv);
+                    }
+                }
+
+                let mv: MoveScorePair;
+
+                if let Some(ponder_mv) = pondered_mv{
+                    mv = ponder_mv;
+                }
+                else{
+                    mv = get_limited_best_move(&mut game_chess_board, &limits);
+                }
 
                 print_move_command_debug(mv.mv);
 
                 tx.send(mv.mv).unwrap();
+
+                // think about the expected reply while the player is thinking
+                let ponder_mv = get_ponder_move(&game_chess_board, mv.mv);
+
+                if ponder_mv != 0{
+                    game_make_move(&mut game_chess_board, mv.mv);
+                    game_make_move(&mut game_chess_board, ponder_mv);
+
+                    let signal = Arc::new(SearchSignal::new(true));
+                    limits.signal = Some(signal.clone());
+
+                    let zobrist_hash = game_chess_board.board.zobrist_hash;
+
+                    let handle = thread::spawn(move || {
+                        return get_limited_best_move(&mut game_chess_board, &limits);
+                    });
+
+                    *thread_ponder_signal.lock().unwrap() = Some(signal.clone());
+
+                    ponder_search = Some(PonderSearch{zobrist_hash, signal, handle});
+                }
             }
         }
     });
 
-    (rx, tx2)
+    (rx, tx2, ponder_signal)
 }
 
 // cmd indices
@@ -137,6 +215,8 @@ fn engine_move_handling() -> (Receiver<u16>, Sender<(GameChessBoard, u32)>) {
 // 2 - flip
 // 3 - fen
 // 4 - time
+// 5 - skill
+// 6 - contempt
 // 9 - quit
 fn engine_update_settings() -> Receiver<String>{
     let (tx, rx): (Sender<String>, Receiver<String>) = channel();
@@ -152,6 +232,8 @@ reset - resets board
 flip - flips board
 fen - takes fen
 time - changes time given to the bot
+skill - changes the skill level of the bot (0-20)
+contempt - changes how much the bot avoids draws (cp)
 
 quit - quit
             ");
@@ -188,6 +270,24 @@ quit - quit
 
                 send_string = format!("4{}", input_string.trim());
             }
+            else if input_string == "skill"{
+                input_string.clear();
+                print!("skill level (0-20) >>");
+                io::stdout().flush().unwrap();
+
+                io::stdin().read_line(&mut input_string).expect("Failed to read line");
+
+                send_string = format!("5{}", input_string.trim());
+            }
+            else if input_string == "contempt"{
+                input_string.clear();
+                print!("contempt (cp) >>");
+                io::stdout().flush().unwrap();
+
+                io::stdin().read_line(&mut input_string).expect("Failed to read line");
+
+                send_string = format!("6{}", input_string.trim());
+            }
             else if input_string == "quit"{
                 send_string = "9whyuhere".to_string();
             }
@@ -219,6 +319,7 @@ fn update_game_settings(
     mut game_settings : ResMut<GameSettings>,
     mut writer: EventWriter<AppExit>,
     cmd_rx: Res<ReceiveMoveTag2>,
+    ponder_signal: Res<PonderSignalTag>,
 ){
     if let Ok(cmd_str) = cmd_rx.0.lock().unwrap().try_recv(){
         let mut board = board.single_mut();
@@ -228,6 +329,13 @@ fn update_game_settings(
         let cmd_type = cmd_str.chars().nth(0).unwrap() as u32 - '0'  as u32;
         let cmd_info = &cmd_str[1..len];
 
+        // reset, flip and fen leave the pondered position behind, so it can stop searching
+        if cmd_type == 1 || cmd_type == 2 || cmd_type == 3{
+            if let Some(signal) = ponder_signal.0.lock().unwrap().take(){
+                signal.stop();
+            }
+        }
+
         // reset
         if cmd_type == 1{
             board.game_board = fen_to_GameChessBoard(DEFAULT_FEN);
@@ -254,6 +362,16 @@ fn update_game_settings(
         else if cmd_type == 4{
             game_settings.think_time = cmd_info.parse().unwrap();
         }
+        else if cmd_type == 5{
+            if let Ok(skill_level) = cmd_info.parse::<u8>(){
+                game_settings.skill_level = skill_level.min(MAX_SKILL_LEVEL);
+            }
+        }
+        else if cmd_type == 6{
+            if let Ok(contempt) = cmd_info.parse::<i16>(){
+                game_settings.contempt = contempt.clamp(-MAX_CONTEMPT, MAX_CONTEMPT);
+            }
+        }
 
         if cmd_type == 9{
             writer.send(AppExit::Success);
@@ -636,6 +754,6 @@ fn update_board_move(
     
     if board_parent.requested_move{
         board_parent.requested_move = false;
-        board_tx.0.lock().unwrap().send((board_parent.game_board.clone(), game_settings.think_time)).unwrap();
+        board_tx.0.lock().unwrap().send((board_parent.game_board.clone(), game_settings.get_search_limits())).unwrap();
     } 
 }
\ No newline at end of file
@@ -18,6 +18,11 @@ pub const QUIESCENCE_DEPTH_LIMIT: u8 = 20;
 pub const BATTLE_DEPTH: u8 = 5;
 pub const BATTLE_THINK_TIME: u16 = 200;
 pub const MAX_SEARCH_EXTENSION: u8 = 3;
-pub const TRANSPOSITION_TABLE_SIZE: usize = 1 << 22;
+pub const DEFAULT_HASH_SIZE_MB: usize = 32;
+pub const MAX_HASH_SIZE_MB: usize = 4096;
+pub const DEFAULT_TABLES_FILE: &'static str = "search_tables.bin";
+// centipawns a draw is worth less than 0 to the side the engine is playing
+pub const DEFAULT_CONTEMPT: i16 = 0;
+pub const MAX_CONTEMPT: i16 = 200;
 
 pub const MOVE_LIMIT_MAX : u16 = 400;
\ No newline at end of file
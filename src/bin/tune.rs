@@ -0,0 +1,392 @@
+// texel tuner for the evaluation parameters
+// usage: tune <positions file> [output file] [max passes]
+
+// File Layout (positions):
+// one position per line, the fen followed by the game result from whites side
+// the result is the last token and can be 1-0, 0-1, 1/2-1/2 or 1.0, 0.0, 0.5
+// brackets, quotes and semicolons around it are ignored, so "fen [0.5]" and epd style "fen c9 \"1-0\";" both work
+
+// the engine modules are shared with the main binary, their warnings already show up there
+#![allow(warnings)]
+
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+#[path = "../board.rs"] mod board;
+#[path = "../move_compute.rs"] mod move_compute;
+#[path = "../functions.rs"] mod functions;
+#[path = "../magic_numbers.rs"] mod magic_numbers;
+#[path = "../engine.rs"] mod engine;
+#[path = "../evaluation.rs"] mod evaluation;
+#[path = "../game_board.rs"] mod game_board;
+#[path = "../zobrist_hash.rs"] mod zobrist_hash;
+#[path = "../transposition_table.rs"] mod transposition_table;
+#[path = "../history_heuristic.rs"] mod history_heuristic;
+#[path = "../see.rs"] mod see;
+#[path = "../uci.rs"] mod uci;
+#[path = "../search_tables.rs"] mod search_tables;
+#[path = "../skill.rs"] mod skill;
+#[path = "../search_stats.rs"] mod search_stats;
+#[path = "../eval_params.rs"] mod eval_params;
+#[path = "../eval_trace.rs"] mod eval_trace;
+#[path = "../eval_score.rs"] mod eval_score;
+#[path = "../eval_accumulator.rs"] mod eval_accumulator;
+#[path = "../pawn_hash_table.rs"] mod pawn_hash_table;
+#[path = "../endgame.rs"] mod endgame;
+#[path = "../kpk_bitbase.rs"] mod kpk_bitbase;
+#[path = "../syzygy.rs"] mod syzygy;
+#[path = "../app_settings.rs"] mod app_settings;
+#[path = "../timer.rs"] mod timer;
+
+use board::*;
+use engine::*;
+use evaluation::*;
+use eval_params::*;
+use search_stats::*;
+use app_settings::*;
+use eval_accumulator::*;
+
+const DEFAULT_MAX_PASSES: u32 = 100;
+
+// parameters start out moving in big steps, the step is halved every time a pass finds nothing better
+const START_STEP: i16 = 8;
+
+// positions used to find parameters that dont change any score
+const ACTIVE_CHECK_SAMPLE: usize = 2000;
+
+struct TuningPosition{
+    board: ChessBoard,
+    // 1.0 white win, 0.5 draw, 0.0 black win
+    result: f64,
+}
+
+fn parse_result(token: &str) -> Option<f64>{
+    match token{
+        "1-0" => return Some(1.0),
+        "0-1" => return Some(0.0),
+        "1/2-1/2" => return Some(0.5),
+        _ => {}
+    }
+
+    match token.parse::<f64>(){
+        Ok(result) if result == 0.0 || result == 0.5 || result == 1.0 => return Some(result),
+        _ => return None,
+    }
+}
+
+fn parse_position_line(line: &str) -> Option<(String, f64)>{
+    let cleaned_line: String = line.chars().map(|c| if c == '[' || c == ']' || c == '"' || c == ';' { ' ' } else { c }).collect();
+
+    let tokens: Vec<&str> = cleaned_line.split_whitespace().collect();
+
+    // board, side to move, castling and en passant are the least a fen needs
+    if tokens.len() < 5{
+        return None;
+    }
+
+    let result = parse_result(tokens[tokens.len() - 1])?;
+
+    // the move counters are optional, anything else after them (epd opcodes) is dropped
+    let mut fen_len = 4;
+
+    while fen_len < 6 && fen_len < tokens.len() - 1 && tokens[fen_len].parse::<u16>().is_ok(){
+        fen_len += 1;
+    }
+
+    return Some((tokens[..fen_len].join(" "), result));
+}
+
+// white relative score, quiescence search is used so hanging pieces dont throw off the static eval
+fn get_quiescence_score(board: &ChessBoard, eval_params: &EvalParams) -> i16{
+    let mut board_copy = board.clone();
+
+    let score = quiescence_search(&mut board_copy, -MATE_SCORE, MATE_SCORE, QUIESCENCE_DEPTH_LIMIT, 0, &mut SearchStats::new(), eval_params);
+
+    return if board.board_color { score } else { -score };
+}
+
+fn get_static_score(board: &ChessBoard, eval_params: &EvalParams) -> i16{
+    let score = get_board_score(board, eval_params);
+
+    return if board.board_color { score } else { -score };
+}
+
+// only quiet positions are kept, for those the static eval is the quiescence resolved score
+// which lets the tuner skip the quiescence search for every error calculation
+fn load_positions(file_path: &str, eval_params: &EvalParams) -> Vec<TuningPosition>{
+    let reader = match File::open(file_path){
+        Ok(file) => BufReader::new(file),
+        Err(error) => {
+            println!("couldnt open {}: {}", file_path, error);
+            return Vec::new();
+        }
+    };
+
+    let mut positions: Vec<TuningPosition> = Vec::new();
+
+    let eval_tables = Arc::new(EvalAccumulatorTables::new(eval_params));
+
+    let mut line_num = 0;
+    let mut invalid_num = 0;
+    let mut not_quiet_num = 0;
+
+    for line in reader.lines(){
+        let line = match line{
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        if line.trim() == "" || line.starts_with('#'){
+            continue;
+        }
+
+        line_num += 1;
+
+        let (fen, result) = match parse_position_line(&line){
+            Some(position) => position,
+            None => {
+                invalid_num += 1;
+                continue;
+            }
+        };
+
+        let mut board = fen_to_board(&fen, &eval_tables);
+
+        update_board(&mut board);
+
+        if board.check_mask != 0 || get_quiescence_score(&board, eval_params) != get_static_score(&board, eval_params){
+            not_quiet_num += 1;
+            continue;
+        }
+
+        positions.push(TuningPosition{board, result});
+    }
+
+    println!("read {} positions, {} invalid, {} not quiet, {} used", line_num, invalid_num, not_quiet_num, positions.len());
+
+    return positions;
+}
+
+fn sigmoid(score: f64, k: f64) -> f64{
+    return 1.0 / (1.0 + 10f64.powf(-k * score / 400.0));
+}
+
+// the boards keep the material and piece square scores of the parameters they were loaded with
+// so they are redone with the current ones
+fn get_scores(positions: &[TuningPosition], eval_params: &EvalParams) -> Vec<i16>{
+    let eval_tables = Arc::new(EvalAccumulatorTables::new(eval_params));
+
+    return positions.iter().map(|position| {
+        let mut board = position.board.clone();
+
+        board.eval_tables = eval_tables.clone();
+        update_eval_accumulators(&mut board);
+
+        return get_static_score(&board, eval_params);
+    }).collect();
+}
+
+// mean squared error between the results and the win probability the scores predict
+fn get_error_from_scores(positions: &[TuningPosition], scores: &[i16], k: f64) -> f64{
+    let mut error_sum: f64 = 0.0;
+
+    for (position, &score) in positions.iter().zip(scores){
+        let error = position.result - sigmoid(score as f64, k);
+
+        error_sum += error * error;
+    }
+
+    return error_sum / positions.len() as f64;
+}
+
+// the positions are split between all cores, evaluating them is what the tuner spends its time on
+fn get_error(positions: &[TuningPosition], eval_params: &EvalParams, k: f64) -> f64{
+    let thread_num = thread::available_parallelism().map(|num| num.get()).unwrap_or(1);
+    let chunk_size = (positions.len() + thread_num - 1) / thread_num;
+
+    if chunk_size == 0{
+        return 0.0;
+    }
+
+    let error_sum: f64 = thread::scope(|scope| {
+        let handles: Vec<_> = positions.chunks(chunk_size).map(|chunk| {
+            scope.spawn(move || {
+                let scores = get_scores(chunk, eval_params);
+
+                return get_error_from_scores(chunk, &scores, k) * chunk.len() as f64;
+            })
+        }).collect();
+
+        return handles.into_iter().map(|handle| handle.join().unwrap()).sum();
+    });
+
+    return error_sum / positions.len() as f64;
+}
+
+// k only scales the scores, so it is fitted once with the starting parameters and then kept fixed
+fn fit_k(positions: &[TuningPosition], eval_params: &EvalParams) -> f64{
+    let scores = get_scores(positions, eval_params);
+
+    let mut best_k: f64 = 1.0;
+    let mut best_error = get_error_from_scores(positions, &scores, best_k);
+
+    let mut step: f64 = 0.1;
+
+    for _ in 0.. 5{
+        let start_k = (best_k - step * 10.0).max(step);
+
+        for step_i in 0.. 21{
+            let k = start_k + step * step_i as f64;
+            let error = get_error_from_scores(positions, &scores, k);
+
+            if error < best_error{
+                best_error = error;
+                best_k = k;
+            }
+        }
+
+        step /= 10.0;
+    }
+
+    return best_k;
+}
+
+// parameters that dont change a single score in the sample (unused terms, squares pieces cant stand on)
+// would only waste two error calculations per pass
+fn get_active_params(positions: &[TuningPosition], eval_params: &EvalParams) -> Vec<(usize, usize)>{
+    let sample = &positions[..positions.len().min(ACTIVE_CHECK_SAMPLE)];
+
+    let base_scores = get_scores(sample, eval_params);
+
+    let mut active_params: Vec<(usize, usize)> = Vec::new();
+
+    let param_lengths: Vec<usize> = eval_params.get_params().iter().map(|(_, values)| values.len()).collect();
+
+    for (param_i, &param_len) in param_lengths.iter().enumerate(){
+        for value_i in 0.. param_len{
+            let mut changed_params = eval_params.clone();
+
+            changed_params.get_params_mut()[param_i].1[value_i] += START_STEP;
+
+            if get_scores(sample, &changed_params) != base_scores{
+                active_params.push((param_i, value_i));
+            }
+        }
+    }
+
+    return active_params;
+}
+
+fn tune(positions: &[TuningPosition], eval_params: &mut EvalParams, k: f64, max_passes: u32, output_path: &str){
+    let active_params = get_active_params(positions, eval_params);
+
+    let param_names: Vec<&'static str> = eval_params.get_params().iter().map(|(name, _)| *name).collect();
+
+    println!("tuning {} active values", active_params.len());
+
+    let mut best_error = get_error(positions, eval_params, k);
+    let mut step = START_STEP;
+
+    for pass in 1..= max_passes{
+        let time_start = Instant::now();
+        let mut improved_num = 0;
+
+        for &(param_i, value_i) in active_params.iter(){
+            let start_value = eval_params.get_params()[param_i].1[value_i];
+
+            for direction in [1, -1]{
+                eval_params.get_params_mut()[param_i].1[value_i] = start_value.saturating_add(step * direction);
+
+                let error = get_error(positions, eval_params, k);
+
+                if error < best_error{
+                    best_error = error;
+                    improved_num += 1;
+                    break;
+                }
+
+                eval_params.get_params_mut()[param_i].1[value_i] = start_value;
+            }
+        }
+
+        println!("pass {} step {} error {:.6} improved {} time {:.1}s", pass, step, best_error, improved_num, time_start.elapsed().as_secs_f32());
+
+        // saved every pass so stopping the tuner early doesnt lose anything
+        match save_eval_params(output_path, eval_params){
+            Ok(()) => {},
+            Err(error) => println!("couldnt save {}: {}", output_path, error),
+        }
+
+        if improved_num == 0{
+            if step == 1{
+                break;
+            }
+
+            step /= 2;
+        }
+    }
+
+    for (param_i, name) in param_names.iter().enumerate(){
+        let start_values = DEFAULT_EVAL_PARAMS.get_params()[param_i].1.to_vec();
+        let changed_num = eval_params.get_params()[param_i].1.iter().zip(start_values.iter()).filter(|(value, start_value)| value != start_value).count();
+
+        if changed_num != 0{
+            println!("{}: {} values changed", name, changed_num);
+        }
+    }
+}
+
+fn main(){
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2{
+        println!("usage: tune <positions file> [output file] [max passes]");
+        return;
+    }
+
+    let positions_path = &args[1];
+    let output_path = if args.len() > 2 { args[2].as_str() } else { DEFAULT_EVAL_FILE };
+
+    let max_passes = match args.get(3){
+        Some(arg) => match arg.parse::<u32>(){
+            Ok(passes) => passes,
+            Err(_) => {
+                println!("max passes has to be a number");
+                return;
+            }
+        },
+        None => DEFAULT_MAX_PASSES,
+    };
+
+    let mut eval_params = EvalParams::new();
+
+    // continue from an earlier tuning run
+    if output_path != positions_path && std::path::Path::new(output_path).exists(){
+        match load_eval_params(output_path, &mut eval_params){
+            Ok(()) => println!("starting from {}", output_path),
+            Err(error) => {
+                println!("couldnt load {}: {}", output_path, error);
+                return;
+            }
+        }
+    }
+
+    let positions = load_positions(positions_path, &eval_params);
+
+    if positions.len() == 0{
+        println!("no positions to tune with");
+        return;
+    }
+
+    let k = fit_k(&positions, &eval_params);
+
+    println!("k {:.4} error {:.6}", k, get_error(&positions, &eval_params, k));
+
+    tune(&positions, &mut eval_params, k, max_passes, output_path);
+
+    println!("tuned parameters saved to {}", output_path);
+}
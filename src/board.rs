@@ -20,7 +20,10 @@
 use crate::move_compute::*;
 use crate::functions::*;
 use crate::zobrist_hash::*;
+use crate::eval_score::*;
+use crate::eval_accumulator::*;
 use crate::app_settings::DEFAULT_FEN;
+use std::sync::Arc;
 
 const PIECE_TYPE_STRING: &str = "PBNRQKpbnrqk/";
 
@@ -70,9 +73,22 @@ pub struct ChessBoard{
     pub board_color: bool,
 
     pub zobrist_hash: u64,
+    // same keys as the zobrist hash but only for the pawns
+    pub pawn_hash: u64,
+
+    // half moves since the last capture or pawn move
+    pub halfmove_clock: u8,
+
+    // updated by make_move like the zobrist hash, white then black, each from the sides own point of view
+    pub material_scores: [EvalScore; 2],
+    pub piece_square_scores: [EvalScore; 2],
+    // PIECE_ENDGAME_WEIGHTS of every piece on the board
+    pub phase_material: i16,
+    // the scores the accumulators above are built from
+    pub eval_tables: Arc<EvalAccumulatorTables>,
 }
 
-pub fn create_empty_board() -> ChessBoard{
+pub fn create_empty_board(eval_tables: &Arc<EvalAccumulatorTables>) -> ChessBoard{
     return ChessBoard{
         piece_bitboards:[0; 12],
         piece_array: [0; 64],
@@ -89,6 +105,14 @@ pub fn create_empty_board() -> ChessBoard{
         board_color: false,
 
         zobrist_hash: 0,
+        pawn_hash: 0,
+
+        halfmove_clock: 0,
+
+        material_scores: [ZERO_EVAL_SCORE; 2],
+        piece_square_scores: [ZERO_EVAL_SCORE; 2],
+        phase_material: 0,
+        eval_tables: eval_tables.clone(),
     }
 }
 
@@ -252,8 +276,8 @@ pub fn print_board_info(chess_board: &ChessBoard){
 
 }
 
-pub fn fen_to_board(fen_string: &str) -> ChessBoard{
-    let mut chess_board: ChessBoard = create_empty_board();
+pub fn fen_to_board(fen_string: &str, eval_tables: &Arc<EvalAccumulatorTables>) -> ChessBoard{
+    let mut chess_board: ChessBoard = create_empty_board(eval_tables);
 
     let mut move_turn: u16 = 0;
     let mut castle_priv: u16 = 0;
@@ -277,7 +301,7 @@ pub fn fen_to_board(fen_string: &str) -> ChessBoard{
             else{
                 if !in_string(PIECE_TYPE_STRING, p){
                     println!("FAILED TO PARSE");
-                    return fen_to_board(DEFAULT_FEN);
+                    return fen_to_board(DEFAULT_FEN, eval_tables);
                 }
 
                 let piece_array_index: i32 = PIECE_TYPE_STRING.chars().position(|c| c == p).unwrap() as i32;
@@ -334,7 +358,9 @@ pub fn fen_to_board(fen_string: &str) -> ChessBoard{
 
         // fen - fifty move rule
         else if fen_string_part == 4{
-            // uhh yeah I'll also do this later. Thanks future me
+            if let Some(digit) = p.to_digit(10){
+                chess_board.halfmove_clock = chess_board.halfmove_clock.saturating_mul(10).saturating_add(digit as u8);
+            }
         }
     }
 
@@ -350,6 +376,9 @@ pub fn fen_to_board(fen_string: &str) -> ChessBoard{
 
     // zobrist
     chess_board.zobrist_hash = get_full_zobrist_hash(&chess_board);
+    chess_board.pawn_hash = get_full_pawn_hash(&chess_board);
+
+    update_eval_accumulators(&mut chess_board);
     
     return chess_board;
 }
@@ -442,8 +471,8 @@ pub fn board_to_fen(chess_board: &ChessBoard) -> String{
         fen_string.push_str("-");
     }
 
-    // umm do this later thx
-    fen_string.push_str(" 0 1");
+    // umm do the full move number later thx
+    fen_string.push_str(&format!(" {} 1", chess_board.halfmove_clock));
 
     return fen_string;
 }
@@ -469,6 +498,14 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
 
     let mut update_zobrist_castle: bool = false;
 
+    // pawn moves and captures reset the fifty move rule
+    if piece_type % 6 == 1 || is_piece_capture{
+        chess_board.halfmove_clock = 0;
+    }
+    else{
+        chess_board.halfmove_clock = chess_board.halfmove_clock.saturating_add(1);
+    }
+
     // normal movement
     chess_board.piece_bitboards[piece_type-1] ^= from_square_bitboard;
     chess_board.piece_bitboards[piece_type-1] ^= to_square_bitboard;
@@ -477,6 +514,13 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
     chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index((piece_type-1) as u8, from_square)];
     chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index((piece_type-1) as u8, to_square)];
 
+    move_piece_eval_scores(chess_board, piece_type-1, from_square as usize, to_square as usize);
+
+    if piece_type % 6 == 1{
+        chess_board.pawn_hash ^= zobrist_hash_table[get_zobrist_piece_index((piece_type-1) as u8, from_square)];
+        chess_board.pawn_hash ^= zobrist_hash_table[get_zobrist_piece_index((piece_type-1) as u8, to_square)];
+    }
+
     // piece is taken
     if is_piece_capture{
         // get rid piece at the bitboard
@@ -513,6 +557,12 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
         
         // updates the piece zobrist
         chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(taken_piece_type, to_square)];
+
+        remove_piece_eval_scores(chess_board, taken_piece_type as usize, to_square as usize);
+
+        if taken_piece_type % 6 == 0{
+            chess_board.pawn_hash ^= zobrist_hash_table[get_zobrist_piece_index(taken_piece_type, to_square)];
+        }
     }
 
     // update castle permission
@@ -613,6 +663,10 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
                 chess_board.black_piece_bitboard ^= capture_bitboard;
 
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(6, to_square+8)];
+
+                remove_piece_eval_scores(chess_board, 6, (to_square + 8) as usize);
+
+                chess_board.pawn_hash ^= zobrist_hash_table[get_zobrist_piece_index(6, to_square+8)];
             }
             else{
                 let capture_bitboard: u64 = 1 << (to_square - 8);
@@ -624,6 +678,10 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
                 chess_board.white_piece_bitboard ^= capture_bitboard;
 
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(0, to_square-8)];
+
+                remove_piece_eval_scores(chess_board, 0, (to_square - 8) as usize);
+
+                chess_board.pawn_hash ^= zobrist_hash_table[get_zobrist_piece_index(0, to_square-8)];
             }
         }
 
@@ -649,6 +707,12 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
 
             // add new piece there
             chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index((promotion_piece_type + switched_piece_color_offset) as u8, to_square)];
+
+            remove_piece_eval_scores(chess_board, piece_type-1, to_square as usize);
+            add_piece_eval_scores(chess_board, (promotion_piece_type + switched_piece_color_offset) as usize, to_square as usize);
+
+            // the pawn is gone
+            chess_board.pawn_hash ^= zobrist_hash_table[get_zobrist_piece_index((piece_type-1) as u8, to_square)];
         }
 
         // castling
@@ -668,6 +732,8 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
                 // update rook zobrist hash
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(3, 56)];
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(3, 59)];
+
+                move_piece_eval_scores(chess_board, 3, 56, 59);
             }
             else if special == 10{
                 chess_board.piece_bitboards[3] ^= WHITE_RIGHT_ROOK_DEFAULT;
@@ -684,6 +750,8 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
                 // update rook zobrist hash
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(3, 63)];
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(3, 61)];
+
+                move_piece_eval_scores(chess_board, 3, 63, 61);
             }
 
             else if special == 11{
@@ -701,6 +769,8 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
                 // update rook zobrist hash
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(9, 0)];
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(9, 3)];
+
+                move_piece_eval_scores(chess_board, 9, 0, 3);
             }
 
             else{
@@ -718,6 +788,8 @@ pub fn make_move(chess_board: &mut ChessBoard, mv: u16){
                 // update rook zobrist hash
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(9, 7)];
                 chess_board.zobrist_hash ^= zobrist_hash_table[get_zobrist_piece_index(9, 5)];
+
+                move_piece_eval_scores(chess_board, 9, 7, 5);
             }
 
             update_zobrist_castle = true;
@@ -854,6 +926,44 @@ pub fn get_board_attack_mask(chess_board: &ChessBoard, color: bool) -> u64{
     return attack_mask;
 }
 
+// every piece of both colors that attacks the square given the occupancy
+pub fn get_square_attackers(board: &ChessBoard, square: usize, occupied: u64) -> u64{
+    let diagonal_sliders: u64 = board.piece_bitboards[1] | board.piece_bitboards[4] | board.piece_bitboards[7] | board.piece_bitboards[10];
+    let straight_sliders: u64 = board.piece_bitboards[3] | board.piece_bitboards[4] | board.piece_bitboards[9] | board.piece_bitboards[10];
+
+    let mut attackers: u64 = 0;
+
+    // a white pawn attacks the square from where a black pawn on the square would attack
+    attackers |= BLACK_PAWN_ATTACK_MASK[square] & board.piece_bitboards[0];
+    attackers |= WHITE_PAWN_ATTACK_MASK[square] & board.piece_bitboards[6];
+
+    attackers |= KNIGHT_MOVE_MASK[square] & (board.piece_bitboards[2] | board.piece_bitboards[8]);
+    attackers |= KING_MOVE_MASK[square] & (board.piece_bitboards[5] | board.piece_bitboards[11]);
+
+    attackers |= get_bishop_move_bitboard(square, occupied) & diagonal_sliders;
+    attackers |= get_rook_move_bitboard(square, occupied) & straight_sliders;
+
+    return attackers & occupied;
+}
+
+// true if the side to move is in check
+// works on boards that havent been updated yet
+pub fn is_king_attacked(board: &ChessBoard) -> bool{
+    let king_square: usize;
+    let opp_pieces: u64;
+
+    if board.board_color{
+        king_square = board.piece_bitboards[5].trailing_zeros() as usize;
+        opp_pieces = board.black_piece_bitboard;
+    }
+    else{
+        king_square = board.piece_bitboards[11].trailing_zeros() as usize;
+        opp_pieces = board.white_piece_bitboard;
+    }
+
+    return get_square_attackers(board, king_square, board.all_piece_bitboard) & opp_pieces != 0;
+}
+
 // update board stuff
 pub fn update_board_attack_mask(chess_board: &mut ChessBoard){
     // update it
@@ -1119,6 +1229,65 @@ pub fn update_board(chess_board: &mut ChessBoard){
     chess_board.is_updated = true;
 }
 
+fn add_pawn_double_moves(chess_board: &ChessBoard, move_buffer: &mut MoveBuffer, piece_color_offset: usize){
+    let mut pawn_double_move_bitboards: u64 = chess_board.piece_bitboards[piece_color_offset];
+
+    if chess_board.board_color{
+        pawn_double_move_bitboards &= WHITE_PAWN_DOUBLE_MOVE_MASK;
+    }
+    else{
+        pawn_double_move_bitboards &= BLACK_PAWN_DOUBLE_MOVE_MASK;
+    }
+
+    while pawn_double_move_bitboards != 0{
+        let square:u8 = pawn_double_move_bitboards.trailing_zeros() as u8;
+
+        add_pawn_double_move(chess_board, move_buffer, square);
+
+        pawn_double_move_bitboards ^= 1<<square;
+    }
+}
+
+fn add_castling_moves(chess_board: &ChessBoard, move_buffer: &mut MoveBuffer){
+    // make sure king is not in check
+    if chess_board.check_mask == 0{
+        if chess_board.board_color{
+
+            // castle left is possible
+            if chess_board.board_info & 0x8 != 0  {
+                // no attack squares - no pieces - not in check
+                if (chess_board.all_piece_bitboard & WHITE_CASTLE_LEFT_BLOCKER_MASK == 0) && (chess_board.attack_mask & WHITE_CASTLE_LEFT_ATTACK_MASK == 0){
+                    move_buffer.add(GET_MOVE_CODE_SPECIAL(60, 58, 9));
+                } 
+            }
+            
+            // castle right is possible
+            if chess_board.board_info & 0x4 != 0{
+                // no attack squares or blockers
+                if (chess_board.all_piece_bitboard|chess_board.attack_mask) & WHITE_CASTLE_RIGHT_BLOCKER_MASK == 0{
+                    move_buffer.add(GET_MOVE_CODE_SPECIAL(60, 62, 10));
+                }
+            }
+        }
+        else{
+            // castle left is possible
+            if chess_board.board_info & 0x2 != 0  {
+                // no attack squares - no pieces - not in check
+                if (chess_board.all_piece_bitboard & BLACK_CASTLE_LEFT_BLOCKER_MASK == 0) && (chess_board.attack_mask & BLACK_CASTLE_LEFT_ATTACK_MASK == 0){
+                    move_buffer.add(GET_MOVE_CODE_SPECIAL(4, 2, 11));
+                }
+            }
+            
+            // castle right is possible
+            if chess_board.board_info & 0x1 != 0{
+                if (chess_board.all_piece_bitboard|chess_board.attack_mask) & BLACK_CASTLE_RIGHT_BLOCKER_MASK == 0{
+                    move_buffer.add(GET_MOVE_CODE_SPECIAL(4, 6, 12));
+                }
+            }
+        }
+    }
+}
+
 pub fn get_capture_moves(chess_board: &mut ChessBoard, move_buffer: &mut MoveBuffer){
     update_board(chess_board);
 
@@ -1147,6 +1316,14 @@ pub fn get_capture_moves(chess_board: &mut ChessBoard, move_buffer: &mut MoveBuf
             }
         }
     }
+    else{
+        // double check only consider king captures
+        let king_square:u8 = chess_board.piece_bitboards[5+piece_color_offset].trailing_zeros() as u8;
+
+        MOVE_FUNCTIONS_ARRAY[5](chess_board, move_buffer, king_square, opp_all_piece_bitboard);
+
+        return;
+    }
 
     let enpassant_square_x: usize = (chess_board.board_info >> 4) as usize;
 
@@ -1281,62 +1458,9 @@ pub fn get_moves(chess_board: &mut ChessBoard, mut move_buffer: &mut MoveBuffer)
         return;
     }
     
-    // handle double pawn moves
-    let mut pawn_double_move_bitboards: u64 = chess_board.piece_bitboards[piece_color_offset];
-
-    if chess_board.board_color{
-        pawn_double_move_bitboards &= WHITE_PAWN_DOUBLE_MOVE_MASK;
-    }
-    else{
-        pawn_double_move_bitboards &= BLACK_PAWN_DOUBLE_MOVE_MASK;
-    }
-
-    while pawn_double_move_bitboards != 0{
-        let square:u8 = pawn_double_move_bitboards.trailing_zeros() as u8;
+    add_pawn_double_moves(chess_board, move_buffer, piece_color_offset);
 
-        add_pawn_double_move(chess_board, move_buffer, square);
-
-        pawn_double_move_bitboards ^= 1<<square;
-    }
-
-    // handle castling
-    // make sure king is not in check
-    if chess_board.check_mask == 0{
-        if chess_board.board_color{
-
-            // castle left is possible
-            if chess_board.board_info & 0x8 != 0  {
-                // no attack squares - no pieces - not in check
-                if (chess_board.all_piece_bitboard & WHITE_CASTLE_LEFT_BLOCKER_MASK == 0) && (chess_board.attack_mask & WHITE_CASTLE_LEFT_ATTACK_MASK == 0){
-                    move_buffer.add(GET_MOVE_CODE_SPECIAL(60, 58, 9));
-                } 
-            }
-            
-            // castle right is possible
-            if chess_board.board_info & 0x4 != 0{
-                // no attack squares or blockers
-                if (chess_board.all_piece_bitboard|chess_board.attack_mask) & WHITE_CASTLE_RIGHT_BLOCKER_MASK == 0{
-                    move_buffer.add(GET_MOVE_CODE_SPECIAL(60, 62, 10));
-                }
-            }
-        }
-        else{
-            // castle left is possible
-            if chess_board.board_info & 0x2 != 0  {
-                // no attack squares - no pieces - not in check
-                if (chess_board.all_piece_bitboard & BLACK_CASTLE_LEFT_BLOCKER_MASK == 0) && (chess_board.attack_mask & BLACK_CASTLE_LEFT_ATTACK_MASK == 0){
-                    move_buffer.add(GET_MOVE_CODE_SPECIAL(4, 2, 11));
-                }
-            }
-            
-            // castle right is possible
-            if chess_board.board_info & 0x1 != 0{
-                if (chess_board.all_piece_bitboard|chess_board.attack_mask) & BLACK_CASTLE_RIGHT_BLOCKER_MASK == 0{
-                    move_buffer.add(GET_MOVE_CODE_SPECIAL(4, 6, 12));
-                }
-            }
-        }
-    }
+    add_castling_moves(chess_board, move_buffer);
 
     // handle enpassant
     let enpassant_square_x: usize = (chess_board.board_info >> 4) as usize;
@@ -1432,4 +1556,85 @@ pub fn get_moves(chess_board: &mut ChessBoard, mut move_buffer: &mut MoveBuffer)
             move_buffer.add(get_move_code_special(passant_square, enpassant_to_square as u8, 3));
         }
     }
-}
\ No newline at end of file
+}
+
+// every legal move that doesnt capture anything (including castling and quiet promotions)
+pub fn get_quiet_moves(chess_board: &mut ChessBoard, move_buffer: &mut MoveBuffer){
+    update_board(chess_board);
+
+    let piece_color_offset: usize;
+
+    if chess_board.board_color{
+        piece_color_offset = 0;
+    }
+    else{
+        piece_color_offset = 6;
+    }
+
+    let empty_squares: u64 = !chess_board.all_piece_bitboard;
+
+    if !chess_board.is_double_check{
+        for piece_type in 0..6{
+            let mut temp_piece_bitboard: u64 = chess_board.piece_bitboards[piece_type + piece_color_offset];
+
+            while temp_piece_bitboard != 0{
+                let square:u8 = temp_piece_bitboard.trailing_zeros() as u8;
+
+                MOVE_FUNCTIONS_ARRAY[piece_type](chess_board, move_buffer, square, empty_squares);
+
+                temp_piece_bitboard ^= 1<<square;
+            }
+        }
+    }
+    else{
+        // double check only consider king movement 
+        let king_square:u8 = chess_board.piece_bitboards[5+piece_color_offset].trailing_zeros() as u8;
+
+        MOVE_FUNCTIONS_ARRAY[5](chess_board, move_buffer, king_square, empty_squares);
+
+        return;
+    }
+
+    add_pawn_double_moves(chess_board, move_buffer, piece_color_offset);
+
+    add_castling_moves(chess_board, move_buffer);
+}
+
+// checks a move that came from somewhere else (tt, killers, counter moves)
+// without generating every move in the position
+pub fn is_move_legal(chess_board: &mut ChessBoard, mv: u16) -> bool{
+    if mv == 0{
+        return false;
+    }
+
+    update_board(chess_board);
+
+    let from_square: u8 = (mv & MOVE_DECODER_MASK) as u8;
+    let to_square: u8 = ((mv >> 6) & MOVE_DECODER_MASK) as u8;
+    let special: u16 = get_move_info(mv);
+
+    let piece_type: u8 = chess_board.piece_array[from_square as usize];
+
+    // no piece or not our piece
+    if piece_type == 0 || ((piece_type - 1) < 6) != chess_board.board_color{
+        return false;
+    }
+
+    let mut move_buffer = MoveBuffer::new();
+
+    // double moves, en passant and castling are rare enough to check the slow way
+    if special == 2 || special == 3 || special >= 9{
+        get_moves(chess_board, &mut move_buffer);
+    }
+    else{
+        let rel_piece_type: usize = ((piece_type - 1) % 6) as usize;
+
+        if chess_board.is_double_check && rel_piece_type != 5{
+            return false;
+        }
+
+        MOVE_FUNCTIONS_ARRAY[rel_piece_type](chess_board, &mut move_buffer, from_square, 1 << to_square);
+    }
+
+    return move_buffer.mv_arr[0..move_buffer.index].contains(&mv);
+}
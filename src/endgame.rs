@@ -0,0 +1,394 @@
+use crate::board::*;
+use crate::eval_params::*;
+use crate::evaluation::*;
+use crate::functions::*;
+use crate::kpk_bitbase::*;
+
+// hand written evaluations for endings the general evaluation gets wrong, looked up by the material on the board
+// and scale factors that pull drawish endgames towards 0
+// the strong side is the one with the extra material, every evaluator scores from its point of view
+
+// positions that are won with correct play, far enough below CHECKMATE_SCORE that they never look like a mate
+pub const KNOWN_WIN_SCORE: i16 = 4000;
+const MAX_ENDGAME_SCORE: i16 = KNOWN_WIN_SCORE * 2;
+
+// the endgame half of the score is multiplied by scale factor / SCALE_FACTOR_NORMAL
+pub const SCALE_FACTOR_NORMAL: i16 = 64;
+pub const SCALE_FACTOR_DRAW: i16 = 0;
+
+const LIGHT_SQUARES_BITBOARD: u64 = 0xAA55AA55AA55AA55;
+const A_FILE_BITBOARD: u64 = 0x0101010101010101;
+const H_FILE_BITBOARD: u64 = 0x8080808080808080;
+
+// number of pawns, bishops, knights, rooks and queens, 4 bits each
+// white in the low 20 bits, black in the 20 above
+pub type MaterialKey = u64;
+
+const MATERIAL_KEY_COLOR_SHIFT: u32 = 20;
+
+pub fn get_material_key(board: &ChessBoard) -> MaterialKey{
+    let mut material_key: MaterialKey = 0;
+
+    for piece_type in 0..5{
+        material_key |= (board.piece_bitboards[piece_type].count_ones() as MaterialKey) << (piece_type * 4);
+        material_key |= (board.piece_bitboards[piece_type + 6].count_ones() as MaterialKey) << (MATERIAL_KEY_COLOR_SHIFT as usize + piece_type * 4);
+    }
+
+    return material_key;
+}
+
+// "KRKP" - the pieces after the first K are the strong sides, the ones after the second K the weak sides
+pub const fn get_signature_material_key(signature: &str, strong_side: bool) -> MaterialKey{
+    let signature_bytes = signature.as_bytes();
+
+    let mut material_key: MaterialKey = 0;
+    let mut color_shift: u32 = if strong_side { 0 } else { MATERIAL_KEY_COLOR_SHIFT };
+
+    let mut i = 1;
+
+    while i < signature_bytes.len(){
+        if signature_bytes[i] == b'K'{
+            color_shift = if strong_side { MATERIAL_KEY_COLOR_SHIFT } else { 0 };
+        }
+        else{
+            let piece_type: u32 = match signature_bytes[i]{
+                b'P' => 0,
+                b'B' => 1,
+                b'N' => 2,
+                b'R' => 3,
+                _ => 4,
+            };
+
+            material_key += 1 << (color_shift + piece_type * 4);
+        }
+
+        i += 1;
+    }
+
+    return material_key;
+}
+
+type EndgameEvaluator = fn(&ChessBoard, bool, &EvalParams) -> i16;
+
+#[derive(Clone, Copy)]
+pub struct EndgameEntry{
+    pub name: &'static str,
+    pub material_key: MaterialKey,
+    pub strong_side: bool,
+    pub evaluator: EndgameEvaluator,
+}
+
+const ENDGAME_EVALUATORS: [(&'static str, EndgameEvaluator); 6] = [
+    ("KBNK", kbnk_score),
+    ("KPK", kpk_score),
+    ("KRKP", krkp_score),
+    ("KQKR", kqkr_score),
+    ("KRKB", krkb_score),
+    ("KRKN", krkn_score),
+];
+
+// every signature once with white as the strong side and once with black
+pub const ENDGAME_REGISTRY: [EndgameEntry; ENDGAME_EVALUATORS.len() * 2] = get_endgame_registry();
+
+const fn get_endgame_registry() -> [EndgameEntry; ENDGAME_EVALUATORS.len() * 2]{
+    let mut endgame_registry = [EndgameEntry{name: "", material_key: 0, strong_side: true, evaluator: kxk_score}; ENDGAME_EVALUATORS.len() * 2];
+
+    let mut i = 0;
+
+    while i < ENDGAME_EVALUATORS.len(){
+        let (name, evaluator) = ENDGAME_EVALUATORS[i];
+
+        endgame_registry[i * 2] = EndgameEntry{name, material_key: get_signature_material_key(name, true), strong_side: true, evaluator};
+        endgame_registry[i * 2 + 1] = EndgameEntry{name, material_key: get_signature_material_key(name, false), strong_side: false, evaluator};
+
+        i += 1;
+    }
+
+    return endgame_registry;
+}
+
+// white relative score and the name of the evaluator that gave it
+// None if no evaluator knows the material on the board
+pub fn probe_endgame_evaluators(board: &ChessBoard, eval_params: &EvalParams) -> Option<(&'static str, i16)>{
+    let white_piece_num = board.white_piece_bitboard.count_ones();
+    let black_piece_num = board.black_piece_bitboard.count_ones();
+
+    // every ending here has a bare king or at most 4 pieces
+    if white_piece_num + black_piece_num > 4 && white_piece_num != 1 && black_piece_num != 1{
+        return None;
+    }
+
+    let material_key = get_material_key(board);
+
+    for endgame_entry in ENDGAME_REGISTRY.iter(){
+        if endgame_entry.material_key != material_key{
+            continue;
+        }
+
+        let score = (endgame_entry.evaluator)(board, endgame_entry.strong_side, eval_params);
+
+        return Some((endgame_entry.name, if endgame_entry.strong_side { score } else { -score }));
+    }
+
+    for strong_side in [true, false]{
+        if has_mating_material_against_bare_king(board, strong_side){
+            let score = kxk_score(board, strong_side, eval_params);
+
+            return Some(("KXK", if strong_side { score } else { -score }));
+        }
+    }
+
+    return None;
+}
+
+fn get_piece_offsets(strong_side: bool) -> (usize, usize){
+    if strong_side{
+        return (0, 6);
+    }
+    else{
+        return (6, 0);
+    }
+}
+
+// squares as if the strong side was white, so its pawns move towards square 0
+fn get_relative_square(square: usize, strong_side: bool) -> usize{
+    if strong_side{
+        return square;
+    }
+    else{
+        return reverse_piece_square_index(square);
+    }
+}
+
+// number of king moves between the squares
+pub fn get_square_distance(square1: usize, square2: usize) -> i16{
+    let x_distance = (square1 as i16 % 8 - square2 as i16 % 8).abs();
+    let y_distance = (square1 as i16 / 8 - square2 as i16 / 8).abs();
+
+    return x_distance.max(y_distance);
+}
+
+// 0 in the center, 120 in the corners
+fn push_to_edge(square: usize) -> i16{
+    let x = square as i16 % 8;
+    let y = square as i16 / 8;
+
+    return ((3 - x).max(x - 4) + (3 - y).max(y - 4)) * 20;
+}
+
+// 120 next to each other, 0 on opposite corners
+fn push_close(square1: usize, square2: usize) -> i16{
+    return (7 - get_square_distance(square1, square2)) * 20;
+}
+
+fn push_away(square1: usize, square2: usize) -> i16{
+    return get_square_distance(square1, square2) * 15;
+}
+
+fn get_non_pawn_material(board: &ChessBoard, piece_offset: usize, eval_params: &EvalParams) -> i16{
+    let mut material: i16 = 0;
+
+    for piece_type in 1..5{
+        material += board.piece_bitboards[piece_offset + piece_type].count_ones() as i16 * eval_params.piece_endgame_values[piece_type];
+    }
+
+    return material;
+}
+
+// the weak side only has its king and the strong side can force a mate without pawns
+fn has_mating_material_against_bare_king(board: &ChessBoard, strong_side: bool) -> bool{
+    let (strong_offset, weak_offset) = get_piece_offsets(strong_side);
+
+    let weak_piece_bitboard = if strong_side { board.black_piece_bitboard } else { board.white_piece_bitboard };
+
+    if weak_piece_bitboard != board.piece_bitboards[weak_offset + 5]{
+        return false;
+    }
+
+    let bishop_bitboard = board.piece_bitboards[strong_offset + 1];
+    let knight_bitboard = board.piece_bitboards[strong_offset + 2];
+
+    // rook or queen
+    if board.piece_bitboards[strong_offset + 3] | board.piece_bitboards[strong_offset + 4] != 0{
+        return true;
+    }
+
+    // bishops on both colours
+    if bishop_bitboard & LIGHT_SQUARES_BITBOARD != 0 && bishop_bitboard & !LIGHT_SQUARES_BITBOARD != 0{
+        return true;
+    }
+
+    return bishop_bitboard != 0 && knight_bitboard != 0;
+}
+
+// drive the king to the edge and bring the own king closer
+pub fn kxk_score(board: &ChessBoard, strong_side: bool, eval_params: &EvalParams) -> i16{
+    let (strong_offset, weak_offset) = get_piece_offsets(strong_side);
+
+    let strong_king_square = board.piece_bitboards[strong_offset + 5].trailing_zeros() as usize;
+    let weak_king_square = board.piece_bitboards[weak_offset + 5].trailing_zeros() as usize;
+
+    let mut score: i16 = KNOWN_WIN_SCORE;
+
+    score += board.piece_bitboards[strong_offset].count_ones() as i16 * eval_params.piece_endgame_values[0];
+    score += get_non_pawn_material(board, strong_offset, eval_params);
+
+    score += push_to_edge(weak_king_square) + push_close(strong_king_square, weak_king_square);
+
+    return score.min(MAX_ENDGAME_SCORE);
+}
+
+// the king can only be mated in a corner the bishop covers
+pub fn kbnk_score(board: &ChessBoard, strong_side: bool, eval_params: &EvalParams) -> i16{
+    let (strong_offset, weak_offset) = get_piece_offsets(strong_side);
+
+    let strong_king_square = board.piece_bitboards[strong_offset + 5].trailing_zeros() as usize;
+    let weak_king_square = board.piece_bitboards[weak_offset + 5].trailing_zeros() as i16;
+
+    // a8 h1 or h8 a1
+    let corner_squares: [i16; 2] = if board.piece_bitboards[strong_offset + 1] & LIGHT_SQUARES_BITBOARD != 0 { [0, 63] } else { [7, 56] };
+
+    let corner_distance = get_manhattan_distance(weak_king_square, corner_squares[0]).min(get_manhattan_distance(weak_king_square, corner_squares[1])) as i16;
+
+    let mut score: i16 = KNOWN_WIN_SCORE + eval_params.piece_endgame_values[1] + eval_params.piece_endgame_values[2];
+
+    score += (14 - corner_distance) * 20 + push_close(strong_king_square, weak_king_square as usize);
+
+    return score;
+}
+
+// exact from the bitbase, wins still prefer the pawn further up
+pub fn kpk_score(board: &ChessBoard, strong_side: bool, eval_params: &EvalParams) -> i16{
+    let (strong_offset, weak_offset) = get_piece_offsets(strong_side);
+
+    let pawn_square = get_relative_square(board.piece_bitboards[strong_offset].trailing_zeros() as usize, strong_side);
+    let strong_king_square = get_relative_square(board.piece_bitboards[strong_offset + 5].trailing_zeros() as usize, strong_side);
+    let weak_king_square = get_relative_square(board.piece_bitboards[weak_offset + 5].trailing_zeros() as usize, strong_side);
+
+    if !probe_kpk_bitbase(strong_king_square, pawn_square, weak_king_square, board.board_color == strong_side){
+        return 0;
+    }
+
+    return KNOWN_WIN_SCORE + eval_params.piece_endgame_values[0] + (6 - (pawn_square / 8) as i16) * 20;
+}
+
+// rook against pawn, won if the kings are badly placed to support the pawn
+pub fn krkp_score(board: &ChessBoard, strong_side: bool, eval_params: &EvalParams) -> i16{
+    let (strong_offset, weak_offset) = get_piece_offsets(strong_side);
+
+    let rook_square = get_relative_square(board.piece_bitboards[strong_offset + 3].trailing_zeros() as usize, strong_side);
+    let strong_king_square = get_relative_square(board.piece_bitboards[strong_offset + 5].trailing_zeros() as usize, strong_side);
+    let weak_king_square = get_relative_square(board.piece_bitboards[weak_offset + 5].trailing_zeros() as usize, strong_side);
+    // moves down the board
+    let pawn_square = get_relative_square(board.piece_bitboards[weak_offset].trailing_zeros() as usize, strong_side);
+
+    let strong_to_move: i16 = if board.board_color == strong_side { 1 } else { 0 };
+
+    let pawn_x = pawn_square % 8;
+    let queening_square = 56 + pawn_x;
+    let rook_value = eval_params.piece_endgame_values[3];
+
+    // the strong king stands in front of the pawn
+    if strong_king_square % 8 == pawn_x && strong_king_square / 8 > pawn_square / 8{
+        return rook_value - get_square_distance(strong_king_square, pawn_square) * 8;
+    }
+
+    // the weak king is too far away from its pawn
+    if get_square_distance(weak_king_square, pawn_square) >= 4 - strong_to_move && get_square_distance(weak_king_square, rook_square) >= 3{
+        return rook_value - get_square_distance(strong_king_square, pawn_square) * 8;
+    }
+
+    // the pawn is far up with its king next to it and the strong king is far away
+    if weak_king_square / 8 >= 5 && get_square_distance(weak_king_square, pawn_square) == 1 && strong_king_square / 8 <= 4 && get_square_distance(strong_king_square, pawn_square) > 2 + strong_to_move{
+        return 80 - get_square_distance(strong_king_square, pawn_square) * 8;
+    }
+
+    let pawn_front_square = pawn_square + 8;
+
+    return 200 - (get_square_distance(strong_king_square, pawn_front_square) - get_square_distance(weak_king_square, pawn_front_square) - get_square_distance(pawn_square, queening_square)) * 8;
+}
+
+// usually won, the king has to be driven to the edge
+pub fn kqkr_score(board: &ChessBoard, strong_side: bool, eval_params: &EvalParams) -> i16{
+    let (strong_offset, weak_offset) = get_piece_offsets(strong_side);
+
+    let strong_king_square = board.piece_bitboards[strong_offset + 5].trailing_zeros() as usize;
+    let weak_king_square = board.piece_bitboards[weak_offset + 5].trailing_zeros() as usize;
+
+    return eval_params.piece_endgame_values[4] - eval_params.piece_endgame_values[3] + push_to_edge(weak_king_square) + push_close(strong_king_square, weak_king_square);
+}
+
+// drawn most of the time, only a king stuck on the edge is in trouble
+pub fn krkb_score(board: &ChessBoard, strong_side: bool, _eval_params: &EvalParams) -> i16{
+    let (_, weak_offset) = get_piece_offsets(strong_side);
+
+    let weak_king_square = board.piece_bitboards[weak_offset + 5].trailing_zeros() as usize;
+
+    return push_to_edge(weak_king_square);
+}
+
+// drawn most of the time, unless the knight gets cut off from its king
+pub fn krkn_score(board: &ChessBoard, strong_side: bool, _eval_params: &EvalParams) -> i16{
+    let (_, weak_offset) = get_piece_offsets(strong_side);
+
+    let weak_king_square = board.piece_bitboards[weak_offset + 5].trailing_zeros() as usize;
+    let knight_square = board.piece_bitboards[weak_offset + 2].trailing_zeros() as usize;
+
+    return push_to_edge(weak_king_square) + push_away(weak_king_square, knight_square);
+}
+
+// how much of the endgame score the strong side keeps, out of SCALE_FACTOR_NORMAL
+pub fn get_scale_factor(board: &ChessBoard, strong_side: bool, eval_params: &EvalParams) -> i16{
+    let (strong_offset, weak_offset) = get_piece_offsets(strong_side);
+
+    let strong_pawn_bitboard = board.piece_bitboards[strong_offset];
+    let strong_bishop_bitboard = board.piece_bitboards[strong_offset + 1];
+    let weak_bishop_bitboard = board.piece_bitboards[weak_offset + 1];
+
+    let strong_material = get_non_pawn_material(board, strong_offset, eval_params);
+    let weak_material = get_non_pawn_material(board, weak_offset, eval_params);
+
+    let bishop_value = eval_params.piece_endgame_values[1];
+
+    // no pawns and at most a minor piece up, it cant be won without pawns
+    if strong_pawn_bitboard == 0 && strong_material - weak_material <= bishop_value{
+        if strong_material < eval_params.piece_endgame_values[3]{
+            return SCALE_FACTOR_DRAW;
+        }
+
+        if weak_material <= bishop_value{
+            return 4;
+        }
+
+        return 14;
+    }
+
+    // opposite coloured bishops, even more drawish when they are all thats left
+    if strong_bishop_bitboard.count_ones() == 1 && weak_bishop_bitboard.count_ones() == 1 && (strong_bishop_bitboard & LIGHT_SQUARES_BITBOARD == 0) != (weak_bishop_bitboard & LIGHT_SQUARES_BITBOARD == 0){
+        if strong_material == bishop_value && weak_material == bishop_value{
+            return 16;
+        }
+
+        return 46;
+    }
+
+    // rook pawns with a bishop that doesnt cover the promotion square, the king just sits in the corner
+    if strong_bishop_bitboard != 0 && strong_material == strong_bishop_bitboard.count_ones() as i16 * bishop_value && weak_material == 0 && strong_pawn_bitboard != 0{
+        let is_single_rook_file = strong_pawn_bitboard & !A_FILE_BITBOARD == 0 || strong_pawn_bitboard & !H_FILE_BITBOARD == 0;
+
+        if is_single_rook_file{
+            let pawn_x = strong_pawn_bitboard.trailing_zeros() as usize % 8;
+            let promotion_square = if strong_side { pawn_x } else { 56 + pawn_x };
+            let weak_king_square = board.piece_bitboards[weak_offset + 5].trailing_zeros() as usize;
+
+            let is_promotion_square_light = (1 << promotion_square) & LIGHT_SQUARES_BITBOARD != 0;
+            let is_wrong_bishop = if is_promotion_square_light { strong_bishop_bitboard & LIGHT_SQUARES_BITBOARD == 0 } else { strong_bishop_bitboard & !LIGHT_SQUARES_BITBOARD == 0 };
+
+            if is_wrong_bishop && get_square_distance(weak_king_square, promotion_square) <= 1{
+                return SCALE_FACTOR_DRAW;
+            }
+        }
+    }
+
+    return SCALE_FACTOR_NORMAL;
+}
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use std::ops::Neg;
 use std::fs;
+use std::sync::{Arc, OnceLock};
 
 
 use crate::app_settings::*;
@@ -10,11 +11,20 @@ use crate::move_compute::*;
 use crate::functions::*;
 use crate::board::*;
 use crate::evaluation::*;
+use crate::kpk_bitbase::*;
+use crate::syzygy::*;
 use crate::game_board::*;
 use crate::zobrist_hash::*;
 use crate::transposition_table::*;
 use crate::timer::*;
 use crate::history_heuristic::*;
+use crate::see::*;
+use crate::uci::*;
+use crate::search_tables::*;
+use crate::skill::*;
+use crate::search_stats::*;
+use crate::eval_params::*;
+use crate::eval_accumulator::*;
 
 
 #[derive(Copy, Clone)]
@@ -117,6 +127,12 @@ const CAPTURE_MOVE_WEIGHTS : [[i8; 6];6]= [
 	[0, 0, 0, 0, 0, 0], // victim King
 ];
 
+// good captures go before everything but the tt move
+const GOOD_CAPTURE_BONUS: i8 = 20;
+
+// losing captures go after the quiet moves
+const LOSING_CAPTURE_WEIGHT: i8 = -60;
+
 fn get_move_weight(mv: u16, board: &ChessBoard) -> i8{
     let to_square: usize = ((mv >> 6) & MOVE_DECODER_MASK) as usize;
     let mut weight : i8 = 0;
@@ -129,11 +145,18 @@ fn get_move_weight(mv: u16, board: &ChessBoard) -> i8{
         let piece_captured: u8 = (board.piece_array[to_square] - 1) % 6;
         let piece_moved: u8 = (board.piece_array[from_square] - 1) % 6;
 
-        weight += CAPTURE_MOVE_WEIGHTS[piece_captured as usize][piece_moved as usize];
+        let capture_weight = CAPTURE_MOVE_WEIGHTS[piece_captured as usize][piece_moved as usize];
+
+        if see(board, mv, 0){
+            weight += GOOD_CAPTURE_BONUS + capture_weight;
+        }
+        else{
+            weight += LOSING_CAPTURE_WEIGHT + capture_weight / 4;
+        }
     }
 
     // going to an attacked square
-    if board.attack_mask & (1 << to_square) != 0{
+    else if board.attack_mask & (1 << to_square) != 0{
         weight -= 20;
     }
 
@@ -153,38 +176,32 @@ fn get_move_weight(mv: u16, board: &ChessBoard) -> i8{
 
 const MAX_MOVE_WEIGHT: i8 = 127;
 
-const TT_MOVE_MOVE_BONUS : i8 = 100;
-const FIRST_KILLER_MOVE_BONUS: i8 = 10;
 const SECOND_KILLER_MOVE_BONUS: i8 = 5;
 
-const COUNTER_MOVE_BONUS: i8 = 7;
+// butterfly history plus both continuation histories
+fn get_quiet_history(board: &ChessBoard, mv: u16, hh_table: &HistoryHueristicTable, history_tables: &SearchHistoryTables, prev_piece_tos: [u16; 2]) -> i16{
+    return hh_table.get(board.board_color, mv) + history_tables.get_continuation(prev_piece_tos, get_piece_to(board, mv));
+}
 
-fn update_move_buffer_weights(move_buffer: &mut MoveBuffer, board: &ChessBoard, tt_move: u16, prev_mv: u16, first_killer: u16, counter_table: &CounterMoveTable, hh_table: &HistoryHueristicTable){
-    let counter_mv = counter_table.get(prev_mv);
-    
+// quiet moves ordered by their static weight and the history heuristics
+fn update_quiet_move_weights(move_buffer: &mut MoveBuffer, board: &ChessBoard, hh_table: &HistoryHueristicTable, history_tables: &SearchHistoryTables, prev_piece_tos: [u16; 2]){
     for i in 0..move_buffer.index{
         let mv = move_buffer.mv_arr[i];
-        
-        if mv == tt_move{
-            move_buffer.mv_weight_arr[i] = TT_MOVE_MOVE_BONUS; // basically forced first
-        }
 
-        else if mv == first_killer{
-            move_buffer.mv_weight_arr[i] = FIRST_KILLER_MOVE_BONUS;
-        }
+        move_buffer.mv_weight_arr[i] = get_move_weight(mv, board);
 
-        // quiet move
-        else{
-            move_buffer.mv_weight_arr[i] = get_move_weight(move_buffer.mv_arr[i], board);
+        let history_score = get_quiet_history(board, mv, hh_table, history_tables, prev_piece_tos) / 35;
 
-            let hh_score = hh_table.get(board.board_color, mv) / 35;
+        move_buffer.mv_weight_arr[i] += history_score as i8;
+    }
+}
 
-            move_buffer.mv_weight_arr[i] += hh_score as i8;
+// orders captures within the good / bad capture stage
+fn add_capture_history_weights(move_buffer: &mut MoveBuffer, board: &ChessBoard, capture_history: &CaptureHistoryTable){
+    for i in 0..move_buffer.index{
+        let capture_score = capture_history.get(board, move_buffer.mv_arr[i]) / 50;
 
-            if mv == counter_mv{
-                move_buffer.mv_weight_arr[i] += COUNTER_MOVE_BONUS;
-            }
-        }        
+        move_buffer.mv_weight_arr[i] = move_buffer.mv_weight_arr[i].saturating_add(capture_score as i8);
     }
 }
 
@@ -212,6 +229,203 @@ fn order_move_buffer(move_buffer: &mut MoveBuffer, curr_index: usize){
     move_buffer.swap(curr_index, best_index);
 }
 
+// move picker stages
+const STAGE_TT_MOVE: u8 = 0;
+const STAGE_GENERATE_CAPTURES: u8 = 1;
+const STAGE_GOOD_CAPTURES: u8 = 2;
+const STAGE_KILLER: u8 = 3;
+const STAGE_COUNTER: u8 = 4;
+const STAGE_GENERATE_QUIETS: u8 = 5;
+const STAGE_QUIETS: u8 = 6;
+const STAGE_BAD_CAPTURES: u8 = 7;
+const STAGE_DONE: u8 = 8;
+
+// hands out the moves of a node one at a time
+// moves only get generated once the earlier stages failed to produce a cutoff
+pub struct MovePicker{
+    stage: u8,
+    tt_mv: u16,
+    killer_mv: u16,
+    counter_mv: u16,
+    capture_buffer: MoveBuffer,
+    bad_capture_buffer: MoveBuffer,
+    quiet_buffer: MoveBuffer,
+    buffer_index: usize,
+    prev_piece_tos: [u16; 2],
+    // number of moves returned so far
+    pub move_num: u8,
+}
+
+impl MovePicker{
+    pub fn new(tt_mv: u16, killer_mv: u16, counter_mv: u16, prev_piece_tos: [u16; 2]) -> MovePicker{
+        MovePicker{
+            stage: STAGE_TT_MOVE,
+            tt_mv,
+            killer_mv,
+            counter_mv,
+            capture_buffer: MoveBuffer::new(),
+            bad_capture_buffer: MoveBuffer::new(),
+            quiet_buffer: MoveBuffer::new(),
+            buffer_index: 0,
+            prev_piece_tos,
+            move_num: 0,
+        }
+    }
+
+    // moves handed out by an earlier stage
+    fn is_special_mv(&self, mv: u16) -> bool{
+        return mv == self.tt_mv || mv == self.killer_mv || mv == self.counter_mv;
+    }
+
+    // killers and counter moves have to be quiet since the captures were already searched
+    fn is_quiet_candidate(&self, mv: u16, chess_board: &mut ChessBoard) -> bool{
+        return mv != self.tt_mv && !is_capture(mv, chess_board) && get_move_info(mv) != 3 && is_move_legal(chess_board, mv);
+    }
+
+    // returns 0 once there are no moves left
+    pub fn next(&mut self, chess_board: &mut ChessBoard, hh_table: &HistoryHueristicTable, history_tables: &SearchHistoryTables) -> u16{
+        let mv = self.next_stage_move(chess_board, hh_table, history_tables);
+
+        if mv != 0{
+            self.move_num += 1;
+        }
+
+        return mv;
+    }
+
+    fn next_stage_move(&mut self, chess_board: &mut ChessBoard, hh_table: &HistoryHueristicTable, history_tables: &SearchHistoryTables) -> u16{
+        loop{
+            match self.stage{
+                STAGE_TT_MOVE => {
+                    self.stage = STAGE_GENERATE_CAPTURES;
+
+                    if is_move_legal(chess_board, self.tt_mv){
+                        return self.tt_mv;
+                    }
+
+                    // a bad tt move shouldnt get skipped in the later stages
+                    self.tt_mv = 0;
+                }
+
+                STAGE_GENERATE_CAPTURES => {
+                    get_capture_moves(chess_board, &mut self.capture_buffer);
+
+                    update_move_buffer_weights_quiescence(&mut self.capture_buffer, chess_board);
+
+                    // losing captures get searched after the quiet moves
+                    // (get_move_weight already pushes them below zero)
+                    let mut good_capture_num: usize = 0;
+
+                    for i in 0..self.capture_buffer.index{
+                        let mv = self.capture_buffer.mv_arr[i];
+                        let weight = self.capture_buffer.mv_weight_arr[i];
+
+                        if weight < 0 && is_capture(mv, chess_board){
+                            self.bad_capture_buffer.add(mv);
+                            self.bad_capture_buffer.mv_weight_arr[self.bad_capture_buffer.index - 1] = weight;
+                        }
+                        else{
+                            self.capture_buffer.mv_arr[good_capture_num] = mv;
+                            self.capture_buffer.mv_weight_arr[good_capture_num] = weight;
+                            good_capture_num += 1;
+                        }
+                    }
+
+                    self.capture_buffer.index = good_capture_num;
+
+                    add_capture_history_weights(&mut self.capture_buffer, chess_board, &history_tables.capture_history);
+                    add_capture_history_weights(&mut self.bad_capture_buffer, chess_board, &history_tables.capture_history);
+
+                    self.buffer_index = 0;
+                    self.stage = STAGE_GOOD_CAPTURES;
+                }
+
+                STAGE_GOOD_CAPTURES => {
+                    if self.buffer_index >= self.capture_buffer.index{
+                        self.stage = STAGE_KILLER;
+                        continue;
+                    }
+
+                    order_move_buffer(&mut self.capture_buffer, self.buffer_index);
+
+                    let mv = self.capture_buffer.mv_arr[self.buffer_index];
+                    self.buffer_index += 1;
+
+                    if mv != self.tt_mv{
+                        return mv;
+                    }
+                }
+
+                STAGE_KILLER => {
+                    self.stage = STAGE_COUNTER;
+
+                    if self.is_quiet_candidate(self.killer_mv, chess_board){
+                        return self.killer_mv;
+                    }
+
+                    self.killer_mv = 0;
+                }
+
+                STAGE_COUNTER => {
+                    self.stage = STAGE_GENERATE_QUIETS;
+
+                    if self.counter_mv != self.killer_mv && self.is_quiet_candidate(self.counter_mv, chess_board){
+                        return self.counter_mv;
+                    }
+
+                    self.counter_mv = 0;
+                }
+
+                STAGE_GENERATE_QUIETS => {
+                    get_quiet_moves(chess_board, &mut self.quiet_buffer);
+
+                    update_quiet_move_weights(&mut self.quiet_buffer, chess_board, hh_table, history_tables, self.prev_piece_tos);
+
+                    self.buffer_index = 0;
+                    self.stage = STAGE_QUIETS;
+                }
+
+                STAGE_QUIETS => {
+                    if self.buffer_index >= self.quiet_buffer.index{
+                        self.buffer_index = 0;
+                        self.stage = STAGE_BAD_CAPTURES;
+                        continue;
+                    }
+
+                    order_move_buffer(&mut self.quiet_buffer, self.buffer_index);
+
+                    let mv = self.quiet_buffer.mv_arr[self.buffer_index];
+                    self.buffer_index += 1;
+
+                    if !self.is_special_mv(mv){
+                        return mv;
+                    }
+                }
+
+                STAGE_BAD_CAPTURES => {
+                    if self.buffer_index >= self.bad_capture_buffer.index{
+                        self.stage = STAGE_DONE;
+                        continue;
+                    }
+
+                    order_move_buffer(&mut self.bad_capture_buffer, self.buffer_index);
+
+                    let mv = self.bad_capture_buffer.mv_arr[self.buffer_index];
+                    self.buffer_index += 1;
+
+                    if mv != self.tt_mv{
+                        return mv;
+                    }
+                }
+
+                _ => {
+                    return 0;
+                }
+            }
+        }
+    }
+}
+
 fn sort_move_vec(move_vec_sorted: &mut Vec<MoveWeightPair>, move_vec: &Vec<u16>, chess_board: &ChessBoard){
     for mv in move_vec{
         move_vec_sorted.push(MoveWeightPair::new(*mv, get_move_weight(*mv, chess_board) as i16));
@@ -438,10 +652,43 @@ pub fn debug_zobrist_hash(board: &mut ChessBoard, depth:u16){
             return;
         }
 
+        if sub_board.pawn_hash != get_full_pawn_hash(&sub_board){
+            println!("pawn hash {} {}", sub_board.pawn_hash, get_full_pawn_hash(&sub_board));
+            println!("prev fen:{} mv:{}", board_to_fen(&board), get_move_string(mv));
+            return;
+        }
+
         debug_zobrist_hash(&mut sub_board, depth - 1);
     } 
 }
 
+// same as debug_zobrist_hash but for the material, piece square and phase accumulators
+pub fn debug_eval_accumulators(board: &mut ChessBoard, eval_params: &EvalParams, depth:u16){
+    // base case
+    if depth == 0{
+        return;
+    }
+
+    let mut move_buffer = MoveBuffer::new();
+
+    get_moves(board, &mut move_buffer);
+    
+    for mv_i in 0..move_buffer.index{
+        let mv = move_buffer.mv_arr[mv_i];
+
+        let mut sub_board: ChessBoard = board.clone();
+
+        make_move(&mut sub_board, mv);
+
+        if !check_eval_accumulators(&sub_board, eval_params){
+            println!("eval accumulators wrong after prev fen:{} mv:{}", board_to_fen(&board), get_move_string(mv));
+            return;
+        }
+
+        debug_eval_accumulators(&mut sub_board, eval_params, depth - 1);
+    } 
+}
+
 // 8/8/2K5/k7/8/8/1Q6/8/ w - - 0 1
 pub fn debug(game_board: &mut GameChessBoard){
     let mut input_string: String = String::new();
@@ -462,13 +709,25 @@ move sequence - make a sequence of moves
 move special - make special move (pawn double, ep, promotion, castling)
 show moves - show possible moves
 show perft - show perft
-show eval - shows curr evaluation
+show eval - shows curr evaluation broken down by term
+show stats - shows the stats of the last search
 show hh_table - shows compressed history heuristic table
 update board - updates board
 endgame weight - endgameness of position
 debug z - debug zobrist
+debug eval - checks the incremental eval scores against recomputing them
 query tt - find entry in TT
 best move - gets best move
+best move skill - gets best move at a skill level (0-20)
+go mate - proves or refutes a mate in n moves
+set hash - resizes and clears the transposition table
+save tables - saves the transposition and history tables to a file
+load tables - loads the transposition and history tables from a file
+save eval - saves the evaluation parameters to a file
+load eval - loads the evaluation parameters from a file
+syzygy path - sets the folders to look for syzygy tablebases in
+probe syzygy - shows the tablebase result of curr board
+uci - switch to uci mode
 bench -best - best move bench
 bench -perft - perft bench
 bench -single -perft - benches current board perft
@@ -495,6 +754,8 @@ bench -single -perft - benches current board perft
         }
 
         else if input_string == "engine info"{
+            let transposition_table = game_board.transposition_table.lock().unwrap();
+
             println!(
                 "
 ENGINE INFO:
@@ -503,7 +764,7 @@ DEFAULT FEN: {}
 MAX SEARCH DEPTH: {}
 QUISCENCE SEARCH DEPTH: {}
 MAX SEARCH EXTENSION: {}
-TRANSPOSITION TABLE SIZE: {}MB
+TRANSPOSITION TABLE SIZE: {:.1}MB ({} entries)
 VERSION: {}
 
 BATTLE INFO:
@@ -515,7 +776,8 @@ BATTLE MOVE LIMIT: {}
                 MAX_SEARCH_DEPTH,
                 QUIESCENCE_DEPTH_LIMIT,
                 MAX_SEARCH_EXTENSION,
-                TRANSPOSITION_TABLE_SIZE * 104 / 8 / (1024*1024),
+                transposition_table.memory_footprint() as f32 / (1024.0 * 1024.0),
+                transposition_table.entry_num(),
                 ENGINE_VERSION,
 
                 BATTLE_THINK_TIME,
@@ -551,11 +813,14 @@ BATTLE MOVE LIMIT: {}
             input_string = input_string.trim().to_string();
 
             if input_string == "default"{
-                *game_board = fen_to_GameChessBoard(&DEFAULT_FEN);
+                set_GameChessBoard_fen(game_board, &DEFAULT_FEN);
             }
             else{
-                *game_board = fen_to_GameChessBoard(&input_string);
+                set_GameChessBoard_fen(game_board, &input_string);
             }
+
+            game_board.transposition_table.lock().unwrap().clear();
+            game_board.hh_table.clear();
         }
 
         else if input_string == "move"{
@@ -632,7 +897,15 @@ BATTLE MOVE LIMIT: {}
         }
 
         else if input_string == "show eval"{
-            println!("Evaluation (rel): {}", get_board_score(&game_board.board));
+            // the attack mask has to be up to date for the attack square terms
+            let mut eval_board = game_board.board.clone();
+            update_board(&mut eval_board);
+
+            get_eval_trace(&eval_board, &game_board.eval_params).show();
+        }
+
+        else if input_string == "show stats"{
+            game_board.search_stats.show();
         }
 
         else if input_string == "update board"{
@@ -641,7 +914,7 @@ BATTLE MOVE LIMIT: {}
         }
 
         else if input_string == "endgame weight"{
-            println!("endgame weight: {}", get_endgame_weight(&game_board.board));
+            println!("endgame weight: {}/{}", get_endgame_weight(&game_board.board), ENDGAME_WEIGHT_MAX);
         }
 
         else if input_string == "debug z"{
@@ -658,6 +931,25 @@ BATTLE MOVE LIMIT: {}
             debug_zobrist_hash(&mut game_board.board, depth);
         }
 
+        else if input_string == "debug eval"{
+            input_string.clear();
+            print!("depth >>");
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let depth: u16 = input_string.trim().parse().expect("cannot parse string to int");
+
+            if check_eval_accumulators(&game_board.board, &game_board.eval_params){
+                debug_eval_accumulators(&mut game_board.board, &game_board.eval_params, depth);
+            }
+            else{
+                println!("eval accumulators wrong at fen:{}", board_to_fen(&game_board.board));
+            }
+        }
+
         else if input_string == "best move"{
             input_string.clear();
             print!("think time (ms) >>");
@@ -674,6 +966,200 @@ BATTLE MOVE LIMIT: {}
             // println!("evaluation: {} score: {} move line: {}", get_move_string(best_move.mv), best_move.score, get_move_line_vec_string(&get_move_line(game_board)));
         }
 
+        else if input_string == "best move skill"{
+            input_string.clear();
+            print!("skill level (0-20) >>");
+            io::stdout().flush().unwrap();
+
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let skill_level: u8 = input_string.trim().parse().expect("cannot parse string to int");
+
+            input_string.clear();
+            print!("think time (ms) >>");
+            io::stdout().flush().unwrap();
+
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let think_time: u32 = input_string.trim().parse().expect("cannot parse string to int");
+
+            let limits = SearchLimits::with_skill(think_time, SkillLevel::new(skill_level.min(MAX_SKILL_LEVEL)));
+
+            get_limited_best_move(game_board, &limits);
+        }
+
+        else if input_string == "go mate"{
+            input_string.clear();
+            print!("mate in (moves) >>");
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let mate_moves = input_string.trim().parse::<u32>().expect("cannot parse string to int").min(MAX_MATE_MOVES as u32) as u8;
+
+            input_string.clear();
+            print!("think time (ms) >>");
+            io::stdout().flush().unwrap();
+
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let think_time: u32 = input_string.trim().parse().expect("cannot parse string to int");
+
+            let limits = SearchLimits::mate(think_time, mate_moves);
+            let mate_moves = limits.mate_moves;
+
+            let mate_mvel: MoveScorePair = get_limited_best_move(game_board, &limits);
+
+            if mate_mvel.mv == 0{
+                println!("no mate in {}", mate_moves);
+            }
+            else{
+                println!("best move: {} {}", get_move_string(mate_mvel.mv), get_score_string(mate_mvel.score));
+            }
+        }
+
+        else if input_string == "set hash"{
+            input_string.clear();
+            print!("size (MB) >>");
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let size_mb: usize = input_string.trim().parse().expect("cannot parse string to int");
+
+            let mut transposition_table = game_board.transposition_table.lock().unwrap();
+
+            transposition_table.resize(size_mb.clamp(1, MAX_HASH_SIZE_MB));
+
+            println!("TRANSPOSITION TABLE RESIZED TO {:.1}MB", transposition_table.memory_footprint() as f32 / (1024.0 * 1024.0));
+        }
+
+        else if input_string == "save tables"{
+            input_string.clear();
+            print!("file path (empty for {}) >>", DEFAULT_TABLES_FILE);
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let mut file_path = input_string.trim();
+
+            if file_path == ""{
+                file_path = DEFAULT_TABLES_FILE;
+            }
+
+            match save_search_tables(file_path, &game_board.transposition_table.lock().unwrap(), &game_board.hh_table){
+                Ok(()) => println!("SEARCH TABLES SAVED TO {}", file_path),
+                Err(error) => println!("could not save search tables: {}", error),
+            }
+        }
+
+        else if input_string == "load tables"{
+            input_string.clear();
+            print!("file path (empty for {}) >>", DEFAULT_TABLES_FILE);
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let mut file_path = input_string.trim();
+
+            if file_path == ""{
+                file_path = DEFAULT_TABLES_FILE;
+            }
+
+            match load_search_tables(file_path, &mut game_board.transposition_table.lock().unwrap(), &mut game_board.hh_table){
+                Ok(()) => println!("SEARCH TABLES LOADED FROM {}", file_path),
+                Err(error) => println!("could not load search tables: {}", error),
+            }
+        }
+
+        else if input_string == "save eval"{
+            input_string.clear();
+            print!("file path (empty for {}) >>", DEFAULT_EVAL_FILE);
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let mut file_path = input_string.trim();
+
+            if file_path == ""{
+                file_path = DEFAULT_EVAL_FILE;
+            }
+
+            match save_eval_params(file_path, &game_board.eval_params){
+                Ok(()) => println!("EVAL PARAMS SAVED TO {}", file_path),
+                Err(error) => println!("could not save eval params: {}", error),
+            }
+        }
+
+        else if input_string == "load eval"{
+            input_string.clear();
+            print!("file path (empty for {}) >>", DEFAULT_EVAL_FILE);
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let mut file_path = input_string.trim();
+
+            if file_path == ""{
+                file_path = DEFAULT_EVAL_FILE;
+            }
+
+            match load_eval_params(file_path, &mut game_board.eval_params){
+                Ok(()) => {
+                    update_GameChessBoard_eval_params(game_board);
+                    println!("EVAL PARAMS LOADED FROM {}", file_path);
+                },
+                Err(error) => println!("could not load eval params: {}", error),
+            }
+        }
+
+        else if input_string == "syzygy path"{
+            input_string.clear();
+            print!("folders (empty to turn off) >>");
+            io::stdout().flush().unwrap();
+            
+            io::stdin()
+            .read_line(&mut input_string)
+            .expect("Failed to read line");
+
+            let (wdl_count, dtz_count) = init_tablebases(input_string.trim());
+
+            println!("LOADED {} WDL AND {} DTZ TABLEBASES, MAX PIECES: {}", wdl_count, dtz_count, get_tb_max_cardinality());
+        }
+
+        else if input_string == "probe syzygy"{
+            match (probe_wdl(&game_board.board), probe_dtz(&game_board.board)){
+                (Some(wdl), Some(dtz)) => println!("wdl: {} dtz: {}", wdl, dtz),
+                (Some(wdl), None) => println!("wdl: {} dtz: not found", wdl),
+                _ => println!("not in the tablebases"),
+            }
+        }
+
+        else if input_string == "uci"{
+            uci_loop(game_board);
+
+            // the gui decides when the engine quits
+            debug_running = false;
+        }
+
         else if input_string == "move sequence"{
             input_string.clear();
             print!("move sequence >>");
@@ -733,9 +1219,12 @@ BATTLE MOVE LIMIT: {}
 
             let true_hash = zob_hash ^ REPETITION_COUNT_HASHES[rep_count as usize]; 
 
-            let tt_entry = game_board.transposition_table.get(true_hash);
-
-            tt_entry.print_entry();
+            if let Some(tt_entry) = game_board.transposition_table.lock().unwrap().get(true_hash){
+                tt_entry.print_entry();
+            }
+            else{
+                println!("no entry found");
+            }
 
 
             
@@ -831,7 +1320,7 @@ pub fn position_bench(flag: u8){
         let t_start = Instant::now();
         
         if flag == 0{
-            negamax_search(&mut game_board.board, &mut game_board.game_tree, &mut game_board.transposition_table, &mut KillerMoveTable::new(), &mut CounterMoveTable::new(), &mut game_board.hh_table, 6, 0, 0, -INF, INF, &Timer::new(Duration::from_secs(10)), &mut node_counter, 0, 0);
+            negamax_search(&mut game_board.board, &mut game_board.game_tree, &mut game_board.transposition_table.lock().unwrap(), &mut KillerMoveTable::new(), &mut CounterMoveTable::new(), &mut game_board.hh_table, &mut SearchHistoryTables::new(), &mut SearchStack::new(), 6, 0, 0, -INF, INF, &Timer::new(Duration::from_secs(10)), &mut node_counter, &mut game_board.search_stats, &game_board.eval_params, 0, [NO_PIECE_TO; 2], 0, false);
         }
         else if flag == 1{
             
@@ -842,11 +1331,11 @@ pub fn position_bench(flag: u8){
         let time_taken = t_start.elapsed().as_millis();
         total_time_taken += t_start.elapsed();
         
-        println!("test: {} took: {}ms nds:{} nds/s: {}", fen_pos, time_taken, node_counter, node_counter as u128 /time_taken * 1000);
+        println!("test: {} took: {}ms nds:{} nds/s: {}", fen_pos, time_taken, node_counter, node_counter as u128 * 1000 / time_taken.max(1));
         total_node_counter += node_counter;
     }
 
-    println!("total: {} took: {}ms ave nds: {} ave nds/s: {}", total_node_counter, total_time_taken.as_millis(), total_node_counter / 20, total_node_counter as u128 / total_time_taken.as_millis() * 1000);
+    println!("total: {} took: {}ms ave nds: {} ave nds/s: {}", total_node_counter, total_time_taken.as_millis(), total_node_counter / 20, total_node_counter as u128 * 1000 / total_time_taken.as_millis().max(1));
 }
 
 pub fn self_battle(game_chess_board: &mut GameChessBoard, time_alloc: u32){
@@ -934,21 +1423,67 @@ pub fn debug_log(logs: &str,  ply: u8){
         .expect("write failed");
 }
 
-pub const CHECKMATE_SCORE: i16 = 9990;
+// a checkmate at ply p scores -MATE_SCORE + p for the mated side
+pub const MATE_SCORE: i16 = 10000;
+
+// anything above this is a forced mate
+// leaves room for mates found up to 200 plies from the root
+pub const CHECKMATE_SCORE: i16 = MATE_SCORE - 200;
+
+// go mate stays inside the plies a mate score can hold
+pub const MAX_MATE_MOVES: u8 = 60;
+// uci go mate without a movetime only stops once a mate is proven or refuted, or it gets told to stop
+pub const MATE_SEARCH_TIME: u32 = u32::MAX;
 pub const STATIC_MOVE_PRUNING_MARGIN : i16 = 150;
 
-pub fn discredit_score(score: i16) -> i16{
-    // probably a checkmate
-    if score < -CHECKMATE_SCORE || score > CHECKMATE_SCORE{
-        if score > 0{
-            return score - 1;
-        }
-        else{
-            return score + 1;
-        }
+pub fn is_mate_score(score: i16) -> bool{
+    return (score > CHECKMATE_SCORE && score <= MATE_SCORE) || (score < -CHECKMATE_SCORE && score >= -MATE_SCORE);
+}
+
+// mate scores are stored relative to the node instead of the root
+// so the same position found at a different ply gets the right distance
+pub fn score_to_tt(score: i16, ply: u8) -> i16{
+    if !is_mate_score(score){
+        return score;
+    }
+
+    if score > 0{
+        return score + ply as i16;
+    }
+    else{
+        return score - ply as i16;
+    }
+}
+
+pub fn score_from_tt(score: i16, ply: u8) -> i16{
+    if !is_mate_score(score){
+        return score;
+    }
+
+    if score > 0{
+        return score - ply as i16;
+    }
+    else{
+        return score + ply as i16;
     }
+}
 
-    return score;
+// moves until mate (negative when we are getting mated)
+pub fn get_mate_distance(score: i16) -> i16{
+    if score > 0{
+        return (MATE_SCORE - score + 1) / 2;
+    }
+    else{
+        return -(MATE_SCORE + score) / 2;
+    }
+}
+
+pub fn get_score_string(score: i16) -> String{
+    if is_mate_score(score){
+        return format!("mate {}", get_mate_distance(score));
+    }
+
+    return format!("cp {}", score);
 }
 
 const ATTACKER_SCORE : [i8; 5] = [0, 2, 2, 4, 9];
@@ -1013,19 +1548,115 @@ pub fn get_search_extention(board: &ChessBoard) -> bool{
 
 const INF: i16 = 32767;
 
+// what a single search is allowed to use
+#[derive(Clone)]
+pub struct SearchLimits{
+    pub time_alloc: u32,
+    pub max_depth: u8,
+    // 0 means no node limit
+    pub max_nodes: u64,
+    // picks among the best few root moves instead of the best one
+    pub skill: Option<SkillLevel>,
+    // how much the engine avoids draws
+    pub contempt: i16,
+    // set when the search can be stopped or is pondering
+    pub signal: Option<Arc<SearchSignal>>,
+    // proves a mate in this many moves instead of a normal search, 0 for a normal search
+    pub mate_moves: u8,
+    // sends uci info lines instead of the repl prints
+    pub uci_info: bool,
+}
+
+impl SearchLimits{
+    pub fn new(time_alloc: u32) -> SearchLimits{
+        SearchLimits{time_alloc, max_depth: MAX_SEARCH_DEPTH, max_nodes: 0, skill: None, contempt: DEFAULT_CONTEMPT, signal: None, mate_moves: 0, uci_info: false}
+    }
+
+    pub fn mate(time_alloc: u32, mate_moves: u8) -> SearchLimits{
+        let mut limits = SearchLimits::new(time_alloc);
+        limits.mate_moves = mate_moves.clamp(1, MAX_MATE_MOVES);
+
+        return limits;
+    }
+
+    pub fn with_skill(time_alloc: u32, skill: SkillLevel) -> SearchLimits{
+        if skill.is_full_strength(){
+            return SearchLimits::new(time_alloc);
+        }
+
+        SearchLimits{time_alloc, max_depth: skill.get_max_depth(), max_nodes: skill.get_max_nodes(), skill: Some(skill), contempt: DEFAULT_CONTEMPT, signal: None, mate_moves: 0, uci_info: false}
+    }
+
+    pub fn get_timer(&self) -> Timer{
+        if let Some(signal) = &self.signal{
+            return Timer::with_signal(Duration::from_millis(self.time_alloc as u64), signal.clone());
+        }
+
+        return Timer::new(Duration::from_millis(self.time_alloc as u64));
+    }
+}
+
 pub fn get_best_move(game_chess_board: &mut GameChessBoard, time_alloc: u32) -> MoveScorePair{
-    let best_move = iterative_deepening(&mut game_chess_board.board, &mut game_chess_board.game_tree, &mut game_chess_board.transposition_table, &mut game_chess_board.hh_table, time_alloc);
+    return get_limited_best_move(game_chess_board, &SearchLimits::new(time_alloc));
+}
+
+pub fn get_limited_best_move(game_chess_board: &mut GameChessBoard, limits: &SearchLimits) -> MoveScorePair{
+    if limits.mate_moves != 0{
+        return get_mate(game_chess_board, limits.mate_moves, &limits.get_timer(), limits.uci_info);
+    }
+
+    // held for the whole search, other clones of the board wait until it is done
+    let mut transposition_table = game_chess_board.transposition_table.lock().unwrap();
+
+    let best_move = iterative_deepening(&mut game_chess_board.board, &mut game_chess_board.game_tree, &mut transposition_table, &mut game_chess_board.hh_table, &mut game_chess_board.search_stats, &game_chess_board.eval_params, limits);
 
     // println!("{}", get_move_line_vec_string(&get_move_line(game_chess_board)));
     return best_move;
 }
 
+// the reply we expect after mv, read out of the transposition table
+// 0 if the table doesnt have a legal one
+pub fn get_ponder_move(game_chess_board: &GameChessBoard, mv: u16) -> u16{
+    if mv == 0{
+        return 0;
+    }
+
+    let mut sub_board = game_chess_board.board.clone();
+    make_move(&mut sub_board, mv);
+
+    // the search adds the position to the game tree before probing
+    let repetition = get_position_counter(&game_chess_board.game_tree, sub_board.zobrist_hash) + 1;
+
+    if repetition as usize >= REPETITION_COUNT_HASHES.len(){
+        return 0;
+    }
+
+    let true_hash = sub_board.zobrist_hash ^ REPETITION_COUNT_HASHES[repetition as usize];
+
+    if let Some(tt_entry) = game_chess_board.transposition_table.lock().unwrap().get(true_hash){
+        let mut move_buffer = MoveBuffer::new();
+
+        get_moves(&mut sub_board, &mut move_buffer);
+
+        if move_buffer.mv_arr[0..move_buffer.index].contains(&tt_entry.best_move){
+            return tt_entry.best_move;
+        }
+    }
+
+    return 0;
+}
+
 // testing fens:
 // 2Q2nk1/p4p1p/1p2rnp1/3p4/3P3q/BP6/P2N4/2K2R b - - - 
 
 // heavily inspired by pleco engine... again
-pub fn iterative_deepening(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64, u8>, transposition_table: &mut TranspositionTable, hh_table: &mut HistoryHueristicTable, time_alloc: u32) -> MoveScorePair{
-    let timer: Timer = Timer::new(Duration::from_millis(time_alloc as u64));
+pub fn iterative_deepening(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64, u8>, transposition_table: &mut TranspositionTable, hh_table: &mut HistoryHueristicTable, stats: &mut SearchStats, eval_params: &EvalParams, limits: &SearchLimits) -> MoveScorePair{
+    let mut timer = limits.get_timer();
+
+    // entries from earlier searches become easier to replace
+    transposition_table.new_search();
+
+    *stats = SearchStats::new();
 
     let depth: u8 = 7;
     let mut best_mvel = MoveScorePair::new(0, -INF);
@@ -1045,19 +1676,66 @@ pub fn iterative_deepening(chess_board: &mut ChessBoard, game_tree: &mut HashMap
     let mut move_vec_sorted: Vec<MoveWeightPair> = Vec::new();
     sort_move_vec(&mut move_vec_sorted, &move_vec_unsorted, chess_board);
 
+    // tablebase root, wins and losses get played straight away
+    // otherwise only the moves that keep the best result get searched
+    if let Some(tb_ranks) = get_tb_root_move_ranks(chess_board, &move_vec_unsorted, game_tree){
+        stats.tb_hits += tb_ranks.len() as u64;
+
+        if let Some(&best_rank) = tb_ranks.iter().max(){
+            let best_moves: Vec<u16> = move_vec_unsorted.iter().zip(&tb_ranks).filter(|(_, &rank)| rank == best_rank).map(|(&mv, _)| mv).collect();
+
+            if best_rank >= TB_RANK_BOUND || best_rank <= -TB_RANK_BOUND{
+                let tb_score = if best_rank > 0 { TB_WIN_SCORE - 1 } else { -TB_WIN_SCORE + 1 };
+
+                if limits.uci_info{
+                    print_uci_info(1, stats, tb_score, 0, &timer, transposition_table.hashfull(), &[best_moves[0]]);
+                }
+                else{
+                    println!("TABLEBASE MOVE {} dtz: {} eval: {}", get_move_string(best_moves[0]), get_tb_rank_dtz(best_rank), get_score_string(tb_score));
+                }
+
+                return MoveScorePair::new(best_moves[0], tb_score);
+            }
+
+            move_vec_sorted.retain(|pair| best_moves.contains(&pair.mv));
+        }
+    }
+
     let mut node_counter = 0;
+    let mut total_nodes: u64 = 0;
+
+    // root moves with their scores from the last fully searched depth
+    let mut root_moves: Vec<MoveScorePair> = Vec::new();
 
     let mut killer_mv_table = KillerMoveTable::new();
     let mut counter_mv_table = CounterMoveTable::new();
+    let mut history_tables = SearchHistoryTables::new();
+    let mut search_stack = SearchStack::new();
+
+    search_stack.set_contempt(chess_board.board_color, limits.contempt);
+
+    update_board(chess_board);
+
+    if chess_board.check_mask == 0{
+        search_stack.set_static_eval(0, get_board_score(chess_board, eval_params));
+    }
 
-    while curr_depth < MAX_SEARCH_DEPTH{
+    while curr_depth < MAX_SEARCH_DEPTH && curr_depth <= limits.max_depth{
         killer_mv_table.clear();
         node_counter = 0;
 
         // debug_print(&"{", curr_depth);
-        if timer.time_out(){
+        if timer.time_out() || (limits.max_nodes != 0 && total_nodes >= limits.max_nodes){
             break;
         }
+
+        // node_counter starts at zero every depth so it only gets whats left
+        if limits.max_nodes != 0{
+            timer.set_node_limit(limits.max_nodes - total_nodes);
+        }
+
+        // the search got cut off before every root move was searched
+        let mut depth_stopped = false;
         // Search Starts here
         let mut best_mvel_search_pair : MoveScorePair = MoveScorePair::new(0, -INF);
 
@@ -1067,11 +1745,17 @@ pub fn iterative_deepening(chess_board: &mut ChessBoard, game_tree: &mut HashMap
             
             let mut sub_board: ChessBoard = chess_board.clone();
 
+            let piece_to = get_piece_to(chess_board, mv);
+
             make_move(&mut sub_board, mv);
 
-            let move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, &mut killer_mv_table, &mut counter_mv_table, hh_table, curr_depth - 1, 1, 0, -beta, -alpha, &timer, &mut node_counter, mv, 0);
+            // limited strength needs exact scores for the other root moves as well
+            let root_alpha = if limits.skill.is_some() { -INF } else { alpha };
 
-            if timer.time_out(){
+            let move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, &mut killer_mv_table, &mut counter_mv_table, hh_table, &mut history_tables, &mut search_stack, curr_depth - 1, 1, 0, -beta, -root_alpha, &timer, &mut node_counter, stats, eval_params, mv, [piece_to, NO_PIECE_TO], 0, false);
+
+            if timer.time_out() || timer.node_limit_reached(node_counter){
+                depth_stopped = true;
                 break;
             }
 
@@ -1088,16 +1772,24 @@ pub fn iterative_deepening(chess_board: &mut ChessBoard, game_tree: &mut HashMap
         }
         // Move Search Ends here
 
+        total_nodes += node_counter as u64;
+
 
         // doesnt add anything to the move vec and just sorts the changed values
         sort_move_vec(&mut move_vec_sorted, &Vec::new(), chess_board);
 
         if best_mvel_search_pair.score > beta{
-            println!("RESTART SEARCH - BETA");
+            if !limits.uci_info{
+                println!("RESTART SEARCH - BETA");
+            }
+
             beta = INF;
         }
         else if best_mvel_search_pair.score < alpha{
-            println!("RESTART SEARCH - ALPHA");
+            if !limits.uci_info{
+                println!("RESTART SEARCH - ALPHA");
+            }
+
             alpha = -INF;
         }
         else{
@@ -1106,18 +1798,31 @@ pub fn iterative_deepening(chess_board: &mut ChessBoard, game_tree: &mut HashMap
                 
                 best_mvel = best_mvel_search_pair;
 
-                println!("DEPTH SEARCHED TO {} a:{} b:{} nodes:{} best move: {} eval: {}",curr_depth, alpha, beta, node_counter, get_move_string(best_mvel.mv), best_mvel.score);
+                if !depth_stopped{
+                    root_moves = move_vec_sorted.iter().map(|pair| MoveScorePair::new(pair.mv, pair.weight)).collect();
 
-                let mut pv_line : [u16; 32] = [0; 32];
-                
-                pv_line[0] = best_mvel.mv;
+                    stats.depth = curr_depth;
+                    stats.depth_nodes.push(node_counter as u64);
+                }
+
+                if limits.uci_info{
+                    // a stopped depth only gets reported once, with the move it found so far
+                    let mut pv_line : [u16; 32] = [0; 32];
 
-                let mut temp_board = chess_board.clone();
-                make_move(&mut temp_board, best_mvel.mv);
+                    pv_line[0] = best_mvel.mv;
 
-                // get_pv_line(&mut temp_board, game_tree, transposition_table, curr_depth - 1, 1, &mut pv_line);
+                    if !depth_stopped{
+                        let mut temp_board = chess_board.clone();
+                        make_move(&mut temp_board, best_mvel.mv);
 
-                // print_pv_line(&pv_line);
+                        get_pv_line(&mut temp_board, game_tree, transposition_table, curr_depth - 1, 1, &mut pv_line);
+                    }
+
+                    print_uci_info(curr_depth, stats, best_mvel.score, total_nodes, &timer, transposition_table.hashfull(), &pv_line);
+                }
+                else{
+                    println!("DEPTH SEARCHED TO {} a:{} b:{} nodes:{} best move: {} eval: {}",curr_depth, alpha, beta, node_counter, get_move_string(best_mvel.mv), get_score_string(best_mvel.score));
+                }
 
                 alpha = -INF;
                 beta = INF;
@@ -1130,13 +1835,35 @@ pub fn iterative_deepening(chess_board: &mut ChessBoard, game_tree: &mut HashMap
         }
     }
 
+    stats.nodes = total_nodes;
+
+    // stopped before the first depth finished, any legal move beats no move
+    if best_mvel.mv == 0 && move_vec_sorted.len() != 0{
+        best_mvel = MoveScorePair::new(move_vec_sorted[0].mv, 0);
+    }
+
     let chess_board_repetition: u8 = get_position_counter(game_tree, chess_board.zobrist_hash);
 
     let true_hash = chess_board.zobrist_hash ^ REPETITION_COUNT_HASHES[chess_board_repetition as usize];
 
-    transposition_table.add(true_hash, discredit_score(best_mvel.score), curr_depth, EXACT_BOUND, best_mvel.mv);
+    transposition_table.add(true_hash, score_to_tt(best_mvel.score, 0), curr_depth, EXACT_BOUND, best_mvel.mv);
 
-    println!("{}%", transposition_table.capacity() * 100.0);
+    if !limits.uci_info{
+        println!("hashfull: {}", transposition_table.hashfull());
+    }
+
+    if let Some(skill) = limits.skill{
+        if root_moves.len() != 0{
+            best_mvel = skill.pick_move(&root_moves);
+
+            if limits.uci_info{
+                println!("info string skill level {} picked {} score {}", skill.level, get_uci_move_string(best_mvel.mv), get_score_string(best_mvel.score));
+            }
+            else{
+                println!("SKILL LEVEL {} PICKED {} eval: {}", skill.level, get_move_string(best_mvel.mv), get_score_string(best_mvel.score));
+            }
+        }
+    }
 
     return best_mvel;
 }
@@ -1154,17 +1881,111 @@ const FUTILITY_MARGINS: [i16; 9] = [
 	520, // depth 8
 ];
 
+// reverse futility pruning uses the futility margins by depth, a bit less when improving
+const REVERSE_FUTILITY_DEPTH: u8 = 8;
+const REVERSE_FUTILITY_IMPROVING_MARGIN: i16 = 40;
+
+const RAZORING_DEPTH: u8 = 3;
+const RAZORING_MARGINS: [i16; 4] = [
+	0,
+	250, // depth 1
+	400, // depth 2
+	550, // depth 3
+];
+
+// a capture beating beta by this much at reduced depth most likely beats beta at full depth
+const PROBCUT_DEPTH: u8 = 5;
+const PROBCUT_MARGIN: i16 = 200;
+const PROBCUT_REDUCTION: u8 = 4;
+
+// nodes without a tt move get searched one ply shallower from this depth on
+const IIR_DEPTH: u8 = 4;
+
+// tablebase results are exact, they can stand in for a deeper search
+const TB_DEPTH_BONUS: u8 = 6;
+
 const SINGULAR_EXTENSION_DEPTH : u8 = 4;
 const SINGULAR_MOVE_MARGIN: i16 = 125;
 
-const LMR_REDUCTION: u8 = 1;
+// reduction = LMR_BASE + ln(depth) * ln(move index) / LMR_DIVISOR
+const LMR_BASE: f32 = 0.75;
+const LMR_DIVISOR: f32 = 2.25;
 const LMR_MOVE_NUM: u8 = 3;
-const LMR_LEGAL_MOVE_NUM: u8 = 9;
 const LMR_DEPTH: u8 = 3;
+// history score worth one ply of reduction
+const LMR_HISTORY_DIVISOR: i16 = 500;
+
+static LMR_TABLE: OnceLock<[[u8; 64]; 64]> = OnceLock::new();
+
+// base reduction for the move_i'th move at the given depth
+fn get_lmr_reduction(depth: u8, move_i: u8) -> i16{
+    let lmr_table = LMR_TABLE.get_or_init(|| {
+        let mut table = [[0; 64]; 64];
+
+        for depth in 1..64{
+            for move_i in 1..64{
+                table[depth][move_i] = (LMR_BASE + (depth as f32).ln() * (move_i as f32).ln() / LMR_DIVISOR) as u8;
+            }
+        }
+
+        table
+    });
+
+    return lmr_table[(depth as usize).min(63)][(move_i as usize).min(63)] as i16;
+}
+
+// quiet moves after this many moves get skipped at shallow depths
+// [improving][depth]
+const LMP_DEPTH: u8 = 4;
+const LMP_MOVE_COUNTS: [[u8; 5]; 2] = [
+    [0, 2, 4, 7, 10],
+    [0, 4, 7, 12, 19],
+];
+
+const MAX_PLY: usize = 128;
+
+// information about the positions along the line currently being searched
+pub struct SearchStack{
+    pub static_evals: [i16; MAX_PLY],
+    // what a draw is worth to black / white
+    pub draw_scores: [i16; 2],
+}
+
+impl SearchStack{
+    pub fn new() -> SearchStack{
+        SearchStack{static_evals: [-INF; MAX_PLY], draw_scores: [0; 2]}
+    }
+
+    // the root side sees a draw as -contempt and the other side as +contempt
+    pub fn set_contempt(&mut self, root_color: bool, contempt: i16){
+        self.draw_scores[root_color as usize] = -contempt;
+        self.draw_scores[!root_color as usize] = contempt;
+    }
+
+    pub fn get_draw_score(&self, board_color: bool) -> i16{
+        return self.draw_scores[board_color as usize];
+    }
 
-const QUIET_MOVE_SCORE: i8 = 10;
+    pub fn set_static_eval(&mut self, ply: u8, static_eval: i16){
+        if (ply as usize) < MAX_PLY{
+            self.static_evals[ply as usize] = static_eval;
+        }
+    }
 
-pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64, u8>, transposition_table: &mut TranspositionTable, killer_mv_table: &mut KillerMoveTable, counter_table: &mut CounterMoveTable , hh_table: &mut HistoryHueristicTable, mut depth: u8, ply: u8, mut search_extention_counter: u8, mut alpha: i16, mut beta: i16, timer: &Timer, node_counter: &mut u32, prev_mv: u16, skip_move: u16) -> i16{
+    pub fn get_static_eval(&self, ply: u8) -> i16{
+        if (ply as usize) < MAX_PLY{
+            return self.static_evals[ply as usize];
+        }
+
+        return -INF;
+    }
+}
+
+// captures losing more than this per depth get pruned near the leaves
+const SEE_PRUNING_DEPTH: u8 = 4;
+const SEE_CAPTURE_MARGIN: i16 = 100;
+
+pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64, u8>, transposition_table: &mut TranspositionTable, killer_mv_table: &mut KillerMoveTable, counter_table: &mut CounterMoveTable , hh_table: &mut HistoryHueristicTable, history_tables: &mut SearchHistoryTables, search_stack: &mut SearchStack, mut depth: u8, ply: u8, mut search_extention_counter: u8, mut alpha: i16, mut beta: i16, timer: &Timer, node_counter: &mut u32, stats: &mut SearchStats, eval_params: &EvalParams, prev_mv: u16, prev_piece_tos: [u16; 2], skip_move: u16, cut_node: bool) -> i16{
 
     // check every 2048 nodes if our time runs out
     // heavily inspired by the blunder engine
@@ -1174,29 +1995,52 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
         }
     }
 
+    if timer.node_limit_reached(*node_counter){
+        return -INF;
+    }
+
     *node_counter += 1;
+    stats.update_seldepth(ply);
     
     let chess_board_repetition : u8 = add_to_game_tree(game_tree, chess_board.zobrist_hash);
 
-    if chess_board_repetition >= 3{
+    // repetition, fifty move rule or nobody can mate
+    if chess_board_repetition >= 3 || chess_board.halfmove_clock >= FIFTY_MOVE_HALFMOVES || is_insufficient_material(chess_board){
         remove_from_game_tree(game_tree, chess_board.zobrist_hash);
-        return 0;
+        return search_stack.get_draw_score(chess_board.board_color);
+    }
+
+    // drawn king and pawn vs king, the root still needs a move
+    if ply > 0 && matches!(probe_kpk_board(chess_board), Some((false, _))){
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+        return search_stack.get_draw_score(chess_board.board_color);
+    }
+
+    // mate distance pruning
+    // a shorter mate has already been found so this node can't improve on it
+    alpha = alpha.max(-MATE_SCORE + ply as i16);
+    beta = beta.min(MATE_SCORE - ply as i16 - 1);
+
+    if alpha >= beta{
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+        return alpha;
     }
 
     let true_hash = chess_board.zobrist_hash ^ REPETITION_COUNT_HASHES[chess_board_repetition as usize];
     
-    let tt_entry = transposition_table.get(true_hash);
-
     let mut tt_mv: u16 = 0;
     let mut entry_type : u8 = 0;
     let mut entry_score : i16 = 0;
 
-    // extra check to make sure we have a valid collision
-    if tt_entry.hash == true_hash{
+    stats.tt_probes += 1;
+
+    if let Some(tt_entry) = transposition_table.get(true_hash){
+        stats.tt_hits += 1;
+
         // larger / equal search
         if tt_entry.depth() >= depth{
             entry_type = tt_entry.entry_type();
-            entry_score = tt_entry.score;
+            entry_score = score_from_tt(tt_entry.score, ply);
 
             let mut should_use = false;
 
@@ -1205,21 +2049,22 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
             }
 
             else if entry_type == LOWER_BOUND{
-                if tt_entry.score >= beta{
+                if entry_score >= beta{
                     should_use = true;
                 }
             }
 
             else if entry_type == UPPER_BOUND{
-                if tt_entry.score <= alpha{
+                if entry_score <= alpha{
                     should_use = true;
                 }    
             }
 
             if should_use{
+                stats.tt_cutoffs += 1;
                 remove_from_game_tree(game_tree, chess_board.zobrist_hash);
                 // debug_log(&format!("({},{},{},{})", 3, tt_entry.score, get_move_string(prev_move), chess_board.zobrist_hash), ply);
-                return tt_entry.score;
+                return entry_score;
             }
 
         }
@@ -1228,12 +2073,41 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
     
     
     if depth == 0{
-        let qmove_score = quiescence_search(chess_board, alpha, beta, QUIESCENCE_DEPTH_LIMIT);
+        let qmove_score = quiescence_search(chess_board, alpha, beta, QUIESCENCE_DEPTH_LIMIT, ply, stats, eval_params);
 
         // debug_log(&format!("({},{},{},{})", 1, qmove_score.score, get_move_string(prev_move), chess_board.zobrist_hash), ply);
         remove_from_game_tree(game_tree, chess_board.zobrist_hash);
         return qmove_score;
     }
+
+    // tablebase positions right after a capture or pawn move, so the fifty move rule cant get in the way
+    if ply > 0 && chess_board.halfmove_clock == 0 && get_tb_max_cardinality() != 0{
+        if let Some(wdl) = probe_wdl(chess_board){
+            stats.tb_hits += 1;
+
+            let tb_score: i16;
+            let tb_bound: u8;
+
+            if wdl < WDL_BLESSED_LOSS{
+                tb_score = -TB_WIN_SCORE + ply as i16;
+                tb_bound = UPPER_BOUND;
+            }
+            else if wdl > WDL_CURSED_WIN{
+                tb_score = TB_WIN_SCORE - ply as i16;
+                tb_bound = LOWER_BOUND;
+            }
+            else{
+                tb_score = search_stack.get_draw_score(chess_board.board_color);
+                tb_bound = EXACT_BOUND;
+            }
+
+            if tb_bound == EXACT_BOUND || (tb_bound == LOWER_BOUND && tb_score >= beta) || (tb_bound == UPPER_BOUND && tb_score <= alpha){
+                transposition_table.add(true_hash, score_to_tt(tb_score, ply), depth.saturating_add(TB_DEPTH_BONUS), tb_bound, 0);
+                remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+                return tb_score;
+            }
+        }
+    }
     
     
     let mut best_score : i16 = -INF;
@@ -1245,9 +2119,97 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
     // the window is a null window
     let is_null_window = beta - alpha == 1;
 
-    let mut move_buffer = MoveBuffer::new();
+    update_board(chess_board);
 
-    get_moves(chess_board, &mut move_buffer);
+    let in_check = chess_board.check_mask != 0;
+
+    // the evaluation means nothing while in check
+    let mut static_eval = -INF;
+
+    if !in_check{
+        static_eval = get_board_score(chess_board, eval_params);
+    }
+
+    search_stack.set_static_eval(ply, static_eval);
+
+    // our position got better since our last move
+    let improving = !in_check && ply >= 2 && static_eval > search_stack.get_static_eval(ply - 2);
+
+    // pruning before any moves get generated, never in pv nodes or while in check
+    if is_null_window && !in_check && skip_move == 0{
+        // Reverse Futility Pruning (static null move)
+        // the position is so good that even giving away a margin still beats beta
+        if depth <= REVERSE_FUTILITY_DEPTH && !is_mate_score(beta){
+            let margin = FUTILITY_MARGINS[depth as usize] - REVERSE_FUTILITY_IMPROVING_MARGIN * improving as i16;
+
+            if static_eval - margin >= beta{
+                stats.reverse_futility_prunes += 1;
+                remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+                return static_eval - margin;
+            }
+        }
+
+        // Razoring
+        // the position is so bad that only captures could save it
+        if depth <= RAZORING_DEPTH && static_eval + RAZORING_MARGINS[depth as usize] < alpha{
+            let razor_score = quiescence_search(chess_board, alpha, beta, QUIESCENCE_DEPTH_LIMIT, ply, stats, eval_params);
+
+            if razor_score <= alpha{
+                stats.razoring_prunes += 1;
+                remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+                return razor_score;
+            }
+        }
+
+        // ProbCut
+        // a good capture that beats beta by a margin at reduced depth will most likely beat beta
+        if depth >= PROBCUT_DEPTH && !is_mate_score(beta){
+            let probcut_beta = beta + PROBCUT_MARGIN;
+
+            let mut move_buffer = MoveBuffer::new();
+
+            get_capture_moves(chess_board, &mut move_buffer);
+
+            for mv_i in 0..move_buffer.index{
+                let mv = move_buffer.mv_arr[mv_i];
+
+                // the capture has to win enough material on its own
+                if !see(chess_board, mv, probcut_beta - static_eval){
+                    continue;
+                }
+
+                let child_piece_tos: [u16; 2] = [get_piece_to(chess_board, mv), prev_piece_tos[0]];
+
+                let mut sub_board: ChessBoard = chess_board.clone();
+
+                make_move(&mut sub_board, mv);
+
+                // cheap check first
+                let mut probcut_score = -quiescence_search(&mut sub_board, -probcut_beta, -probcut_beta + 1, QUIESCENCE_DEPTH_LIMIT, ply + 1, stats, eval_params);
+
+                if probcut_score >= probcut_beta{
+                    probcut_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, history_tables, search_stack, depth - PROBCUT_REDUCTION, ply + 1, search_extention_counter, -probcut_beta, -probcut_beta + 1, timer, node_counter, stats, eval_params, mv, child_piece_tos, 0, !cut_node);
+                }
+
+                if probcut_score >= probcut_beta{
+                    stats.probcut_prunes += 1;
+                    remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+
+                    transposition_table.add(true_hash, score_to_tt(probcut_score, ply), depth - PROBCUT_REDUCTION + 1, LOWER_BOUND, mv);
+
+                    return probcut_score;
+                }
+            }
+        }
+    }
+
+    // Internal Iterative Reductions
+    // without a tt move the move ordering is poor, so the shallower search is cheaper
+    // and leaves a tt move behind for the next iteration
+    // only pv and expected cut nodes, all nodes search every move anyway so ordering matters less there
+    if (!is_null_window || cut_node) && tt_mv == 0 && skip_move == 0 && depth >= IIR_DEPTH{
+        depth -= 1;
+    }
 
     // extend search
     if search_extention_counter < MAX_SEARCH_EXTENSION{
@@ -1257,43 +2219,56 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
         }
     }
 
-    // no legal moves
-    if move_buffer.index == 0{
-        // stalemate
-        if chess_board.check_mask == 0{
-            best_score = 0;
-        }
-        
-        // checkmate
-        else{
-            // shift the checkmate so closer checkmates are preffered
-            best_score = -10000 + ply as i16;
-            
-        }
-        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
-
-        return best_score;
-    }
-
-    let first_killer_mv = killer_mv_table.get(ply); 
+    let mut move_picker = MovePicker::new(tt_mv, killer_mv_table.get(ply), counter_table.get(prev_mv), prev_piece_tos);
 
-    // gets the weights for the moves
-    update_move_buffer_weights(&mut move_buffer, chess_board, tt_mv, prev_mv, first_killer_mv, counter_table, hh_table);
-    
-    for move_i in 0..move_buffer.index{        
+    loop{
+        let mv = move_picker.next(chess_board, hh_table, history_tables);
 
-        order_move_buffer(&mut move_buffer, move_i);
+        // no moves left
+        if mv == 0{
+            break;
+        }
 
-        let mv = move_buffer.mv_arr[move_i];
+        let move_i = move_picker.move_num - 1;
 
         if mv == skip_move{
             continue;
         }
+
+        // SEE pruning
+        // skip captures that lose too much material close to the horizon
+        if is_null_window && depth <= SEE_PRUNING_DEPTH && best_score != -INF && chess_board.check_mask == 0 && is_capture(mv, chess_board){
+            if !see(chess_board, mv, -SEE_CAPTURE_MARGIN * depth as i16){
+                stats.see_prunes += 1;
+                continue;
+            }
+        }
         
+        let mv_is_capture = is_capture(mv, chess_board) || get_move_info(mv) == 3;
+        let mv_is_quiet = !mv_is_capture && !is_promotion_mv(mv);
+
+        // Late Move Pruning
+        // at shallow depths quiet moves this late in the ordering are very unlikely to matter
+        if is_null_window && depth <= LMP_DEPTH && !in_check && mv_is_quiet && best_score > -CHECKMATE_SCORE{
+            if move_i >= LMP_MOVE_COUNTS[improving as usize][depth as usize]{
+                stats.late_move_prunes += 1;
+                continue;
+            }
+        }
+
         let mut sub_board: ChessBoard = chess_board.clone();
 
         let mut move_score = 0;
 
+        let piece_to = get_piece_to(chess_board, mv);
+        let child_piece_tos: [u16; 2] = [piece_to, prev_piece_tos[0]];
+
+        let mut history_score: i16 = 0;
+
+        if mv_is_quiet{
+            history_score = get_quiet_history(chess_board, mv, hh_table, history_tables, prev_piece_tos);
+        }
+
         // Singular Extensions
         if move_i == 0 && mv == tt_mv && search_extention_counter <= MAX_SEARCH_EXTENSION && !is_null_window{
             let mut next_depth = depth;
@@ -1303,7 +2278,7 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
                 let score_to_beat = entry_score - SINGULAR_MOVE_MARGIN;
                 let depth_reduction = 3 + depth / 6;
                 
-                let next_best_score = negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, depth - 1 - depth_reduction, ply + 1, search_extention_counter, score_to_beat, score_to_beat+1, timer, node_counter, mv, mv);
+                let next_best_score = negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, history_tables, search_stack, depth - 1 - depth_reduction, ply + 1, search_extention_counter, score_to_beat, score_to_beat+1, timer, node_counter, stats, eval_params, mv, prev_piece_tos, mv, cut_node);
 
                 if next_best_score <= score_to_beat {
                     next_depth += 1;
@@ -1313,42 +2288,72 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
             
             make_move(&mut sub_board, mv);
 
-            move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, next_depth - 1, ply + 1, next_search_extension, -beta, -alpha, timer, node_counter, mv, 0);
+            move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, history_tables, search_stack, next_depth - 1, ply + 1, next_search_extension, -beta, -alpha, timer, node_counter, stats, eval_params, mv, child_piece_tos, 0, false);
         }
         else{
             make_move(&mut sub_board, mv);
 
-            let mut new_depth = depth;
+            // Late Move Reductions
+            // later moves get searched shallower, how much shallower depends on
+            // 1. depth and move index
+            // 2. pv node
+            // 3. improving
+            // 4. checks
+            // 5. history
+            let mut reduction: i16 = 0;
 
-            // CONDITIONS
-            // 1. null window
-            // 2. depth
-            // 3. move index
-            // 4. number legal moves
-            // 5. tactical
+            if depth >= LMR_DEPTH && move_i >= LMR_MOVE_NUM && !is_promotion_mv(mv){
+                reduction = get_lmr_reduction(depth, move_i);
 
-            if is_null_window && depth >= LMR_DEPTH && move_i as u8 >= LMR_MOVE_NUM && move_buffer.index as u8 > LMR_LEGAL_MOVE_NUM{
+                if !is_null_window{
+                    reduction -= 1;
+                }
 
-                let is_tactical = chess_board.check_mask != 0 || is_promotion_mv(mv);
+                if !improving{
+                    reduction += 1;
+                }
 
-                if !is_tactical{
-                    new_depth -= LMR_REDUCTION;
+                if in_check || is_king_attacked(&sub_board){
+                    reduction -= 1;
+                }
+
+                reduction -= history_score / LMR_HISTORY_DIVISOR;
+
+                // never drop straight into the quiescence search
+                reduction = reduction.clamp(0, depth as i16 - 2);
+
+                if reduction > 0{
+                    stats.lmr_reductions += 1;
                 }
             }
 
-            move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, new_depth - 1, ply + 1, search_extention_counter, -(alpha + 1), -alpha, timer, node_counter, mv, 0);
+            // reduced moves are expected to fail high, otherwise cut and all nodes alternate
+            let child_cut_node = reduction > 0 || !cut_node;
+
+            move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, history_tables, search_stack, depth - 1 - reduction as u8, ply + 1, search_extention_counter, -(alpha + 1), -alpha, timer, node_counter, stats, eval_params, mv, child_piece_tos, 0, child_cut_node);
+
+            // the reduced search beat alpha so it has to be searched to the full depth
+            if reduction > 0 && move_score > alpha{
+                stats.lmr_researches += 1;
+                move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, history_tables, search_stack, depth - 1, ply + 1, search_extention_counter, -(alpha + 1), -alpha, timer, node_counter, stats, eval_params, mv, child_piece_tos, 0, !cut_node);
+            }
 
             if move_score > alpha && move_score < beta && !is_null_window{
-                move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, depth - 1, ply + 1, search_extention_counter, -beta, -alpha, timer, node_counter, mv, 0);
+                stats.pvs_researches += 1;
+                move_score = -negamax_search(&mut sub_board, game_tree, transposition_table, killer_mv_table, counter_table, hh_table, history_tables, search_stack, depth - 1, ply + 1, search_extention_counter, -beta, -alpha, timer, node_counter, stats, eval_params, mv, child_piece_tos, 0, false);
             }
         }
 
-        let mv_is_quiet = move_buffer.mv_weight_arr[move_i] < QUIET_MOVE_SCORE;
-                 
         if move_score >= beta{
+            stats.beta_cutoffs += 1;
+
+            if move_i == 0{
+                stats.first_move_cutoffs += 1;
+            }
+
             remove_from_game_tree(game_tree, chess_board.zobrist_hash);
             
-            transposition_table.add(true_hash, discredit_score(move_score), depth, LOWER_BOUND, mv);
+            transposition_table.add(true_hash, score_to_tt(move_score, ply), depth, LOWER_BOUND, mv);
 
             // move is quiet
             if mv_is_quiet{
@@ -1359,6 +2364,10 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
                 // let hh_bonus: i16 = 30 * depth - 25;        
 
                 hh_table.update(chess_board.board_color, mv, hh_bonus);
+                history_tables.update_continuation(prev_piece_tos, piece_to, hh_bonus);
+            }
+            else if mv_is_capture{
+                history_tables.capture_history.update(chess_board, mv, 20 * depth as i16);
             }
 
             if !is_capture(mv, chess_board){
@@ -1375,6 +2384,10 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
             if mv_is_quiet{
                 let hh_penalty: i16 = -5 * depth as i16;
                 hh_table.update(chess_board.board_color, mv, hh_penalty);
+                history_tables.update_continuation(prev_piece_tos, piece_to, hh_penalty);
+            }
+            else if mv_is_capture{
+                history_tables.capture_history.update(chess_board, mv, -5 * depth as i16);
             }
         }
         
@@ -1393,8 +2406,25 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
         }
     }
 
+    // no legal moves
+    if move_picker.move_num == 0{
+        // stalemate
+        if chess_board.check_mask == 0{
+            best_score = search_stack.get_draw_score(chess_board.board_color);
+        }
+        
+        // checkmate
+        else{
+            // shift the checkmate so closer checkmates are preffered
+            best_score = -MATE_SCORE + ply as i16;
+        }
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+
+        return best_score;
+    }
+
     if skip_move == 0{
-        transposition_table.add(true_hash, discredit_score(best_score), depth, tt_entry_type, best_mv);
+        transposition_table.add(true_hash, score_to_tt(best_score, ply), depth, tt_entry_type, best_mv);
     }
 
     remove_from_game_tree(game_tree, chess_board.zobrist_hash);
@@ -1404,10 +2434,24 @@ pub fn negamax_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64,
     return best_score;
 }
 
-pub fn quiescence_search(chess_board: &mut ChessBoard, mut alpha: i16, mut beta: i16, depth: u8) -> i16{  
+// extra room on top of the captured piece before a capture counts as hopeless
+const DELTA_PRUNING_MARGIN: i16 = 200;
+
+// number of quiescence plies that also search quiet checking moves
+const QUIESCENCE_CHECK_DEPTH: u8 = 1;
+
+pub fn quiescence_search(chess_board: &mut ChessBoard, mut alpha: i16, beta: i16, depth: u8, ply: u8, stats: &mut SearchStats, eval_params: &EvalParams) -> i16{  
+    stats.qnodes += 1;
+    stats.update_seldepth(ply);
+
     update_board(chess_board);
 
-    let stand_pat = get_board_score(chess_board);
+    // in check standing pat isnt an option, every evasion has to be looked at
+    if chess_board.check_mask != 0 && depth != 0{
+        return quiescence_evasions(chess_board, alpha, beta, depth, ply, stats, eval_params);
+    }
+
+    let stand_pat = get_board_score(chess_board, eval_params);
 
     if stand_pat >= beta{
         return beta;
@@ -1419,16 +2463,14 @@ pub fn quiescence_search(chess_board: &mut ChessBoard, mut alpha: i16, mut beta:
     
     let mut best_score = stand_pat;
 
-    let mut move_buffer: MoveBuffer = MoveBuffer::new();
+    if depth == 0{
+        return stand_pat;
+    }
 
+    let mut move_buffer: MoveBuffer = MoveBuffer::new();
     
     get_capture_moves(chess_board, &mut move_buffer);
 
-    // no legal moves
-    if move_buffer.index == 0 || depth == 0{
-        return stand_pat;
-    }
-
     update_move_buffer_weights_quiescence(&mut move_buffer, chess_board);
 
     for mv_i in 0.. move_buffer.index{
@@ -1436,11 +2478,30 @@ pub fn quiescence_search(chess_board: &mut ChessBoard, mut alpha: i16, mut beta:
 
         let mv = move_buffer.mv_arr[mv_i];
 
+        // delta pruning
+        // even getting the piece for free doesnt bring the score back up to alpha
+        if !is_promotion_mv(mv){
+            let captured_piece = chess_board.piece_array[((mv >> 6) & MOVE_DECODER_MASK) as usize];
+
+            // en passant lands on an empty square
+            let captured_value = if captured_piece == 0 { eval_params.piece_values[0] } else { eval_params.piece_values[((captured_piece - 1) % 6) as usize] };
+
+            if stand_pat + captured_value + DELTA_PRUNING_MARGIN < alpha{
+                stats.delta_prunes += 1;
+                continue;
+            }
+        }
+
+        // losing captures cant raise the stand pat score
+        if !see(chess_board, mv, 0){
+            continue;
+        }
+
         let mut sub_board: ChessBoard = chess_board.clone();
         
         make_move(&mut sub_board, mv);
         
-        let move_score = -quiescence_search(&mut sub_board, -beta, -alpha, depth - 1);                
+        let move_score = -quiescence_search(&mut sub_board, -beta, -alpha, depth - 1, ply + 1, stats, eval_params);                
 
         if move_score >= beta{
             return move_score;
@@ -1455,9 +2516,247 @@ pub fn quiescence_search(chess_board: &mut ChessBoard, mut alpha: i16, mut beta:
         }
     }
 
+    // quiet checks right after the main search can find mates the captures miss
+    if QUIESCENCE_DEPTH_LIMIT - depth < QUIESCENCE_CHECK_DEPTH{
+        move_buffer = MoveBuffer::new();
+
+        get_quiet_moves(chess_board, &mut move_buffer);
+
+        for mv_i in 0..move_buffer.index{
+            let mv = move_buffer.mv_arr[mv_i];
+
+            // checks that just hang the piece arent worth it
+            if !see(chess_board, mv, 0){
+                continue;
+            }
+
+            let mut sub_board: ChessBoard = chess_board.clone();
+
+            make_move(&mut sub_board, mv);
+
+            if !is_king_attacked(&sub_board){
+                continue;
+            }
+
+            let move_score = -quiescence_search(&mut sub_board, -beta, -alpha, depth - 1, ply + 1, stats, eval_params);
+
+            if move_score >= beta{
+                return move_score;
+            }
+
+            if move_score > best_score{
+                best_score = move_score;
+
+                if best_score > alpha{
+                    alpha = move_score;
+                }
+            }
+        }
+    }
+
+    return best_score;
+}
+
+// every legal move gets searched when the side to move is in check
+fn quiescence_evasions(chess_board: &mut ChessBoard, mut alpha: i16, beta: i16, depth: u8, ply: u8, stats: &mut SearchStats, eval_params: &EvalParams) -> i16{
+    let mut move_buffer: MoveBuffer = MoveBuffer::new();
+
+    get_moves(chess_board, &mut move_buffer);
+
+    // checkmate
+    if move_buffer.index == 0{
+        return -MATE_SCORE + ply as i16;
+    }
+
+    update_move_buffer_weights_quiescence(&mut move_buffer, chess_board);
+
+    let mut best_score = -INF;
+
+    for mv_i in 0..move_buffer.index{
+        order_move_buffer(&mut move_buffer, mv_i);
+
+        let mv = move_buffer.mv_arr[mv_i];
+
+        let mut sub_board: ChessBoard = chess_board.clone();
+
+        make_move(&mut sub_board, mv);
+
+        let move_score = -quiescence_search(&mut sub_board, -beta, -alpha, depth - 1, ply + 1, stats, eval_params);
+
+        if move_score >= beta{
+            return move_score;
+        }
+
+        if move_score > best_score{
+            best_score = move_score;
+
+            if best_score > alpha{
+                alpha = move_score;
+            }
+        }
+    }
+
+    return best_score;
+}
+
+// full width alpha beta that only cares about checkmates
+// no reductions, no pruning and no quiescence so a mate that isnt found doesnt exist
+pub fn mate_search(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64, u8>, depth: u8, ply: u8, mut alpha: i16, mut beta: i16, timer: &Timer, node_counter: &mut u32) -> i16{
+    // the result gets thrown away once the time is up
+    if (*node_counter & 2047) == 0{
+        if timer.time_out(){
+            return 0;
+        }
+    }
+
+    *node_counter += 1;
+
+    let chess_board_repetition : u8 = add_to_game_tree(game_tree, chess_board.zobrist_hash);
+
+    if chess_board_repetition >= 3{
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+        return 0;
+    }
+
+    let mut move_buffer = MoveBuffer::new();
+
+    get_moves(chess_board, &mut move_buffer);
+
+    // no legal moves
+    if move_buffer.index == 0{
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+
+        // stalemate
+        if chess_board.check_mask == 0{
+            return 0;
+        }
+
+        return -MATE_SCORE + ply as i16;
+    }
+
+    // no mate within the horizon
+    if depth == 0{
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+        return 0;
+    }
+
+    alpha = alpha.max(-MATE_SCORE + ply as i16);
+    beta = beta.min(MATE_SCORE - ply as i16 - 1);
+
+    if alpha >= beta{
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+        return alpha;
+    }
+
+    update_move_buffer_weights_quiescence(&mut move_buffer, chess_board);
+
+    let mut best_score = -INF;
+
+    for mv_i in 0..move_buffer.index{
+        order_move_buffer(&mut move_buffer, mv_i);
+
+        let mv = move_buffer.mv_arr[mv_i];
+
+        let mut sub_board: ChessBoard = chess_board.clone();
+
+        make_move(&mut sub_board, mv);
+
+        let move_score = -mate_search(&mut sub_board, game_tree, depth - 1, ply + 1, -beta, -alpha, timer, node_counter);
+
+        if move_score > best_score{
+            best_score = move_score;
+
+            if move_score > alpha{
+                alpha = move_score;
+            }
+        }
+
+        if alpha >= beta{
+            break;
+        }
+    }
+
+    remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+
     return best_score;
 }
 
+// proves or refutes a mate in mate_moves moves
+// returns a null move if there is no mate or the search got stopped before finding one
+pub fn get_mate(game_chess_board: &mut GameChessBoard, mate_moves: u8, timer: &Timer, uci_info: bool) -> MoveScorePair{
+    let chess_board = &mut game_chess_board.board;
+    let game_tree = &mut game_chess_board.game_tree;
+
+    let mut move_buffer = MoveBuffer::new();
+
+    get_moves(chess_board, &mut move_buffer);
+
+    // already mated or stalemated
+    if move_buffer.index == 0{
+        return MoveScorePair::new(0, 0);
+    }
+
+    let mut node_counter: u32 = 0;
+
+    // shorter mates are tried first so the first mate found is the quickest
+    for mate_depth in 1..=mate_moves{
+        let ply_depth = mate_depth * 2 - 1;
+
+        let mut alpha = -INF;
+        let mut best_mvel = MoveScorePair::new(0, -INF);
+
+        for mv_i in 0..move_buffer.index{
+            let mv = move_buffer.mv_arr[mv_i];
+
+            let mut sub_board: ChessBoard = chess_board.clone();
+
+            make_move(&mut sub_board, mv);
+
+            let move_score = -mate_search(&mut sub_board, game_tree, ply_depth - 1, 1, -INF, -alpha, timer, &mut node_counter);
+
+            if timer.time_out(){
+                if uci_info{
+                    println!("info string mate search stopped at mate in {} nodes {}", mate_depth, node_counter);
+                }
+                else{
+                    println!("MATE SEARCH STOPPED AT {} nodes:{}", mate_depth, node_counter);
+                }
+
+                return MoveScorePair::new(0, 0);
+            }
+
+            if move_score > best_mvel.score{
+                best_mvel = MoveScorePair::new(mv, move_score);
+
+                if move_score > alpha{
+                    alpha = move_score;
+                }
+            }
+        }
+
+        if uci_info{
+            let elapsed_ms = timer.get_elapsed_time().as_millis().max(1) as u64;
+
+            // a refuted depth has no real score, only the mate gets one
+            if best_mvel.score > CHECKMATE_SCORE{
+                println!("info depth {} score {} nodes {} nps {} time {} pv {}", ply_depth, get_score_string(best_mvel.score), node_counter, node_counter as u64 * 1000 / elapsed_ms, elapsed_ms, get_uci_move_string(best_mvel.mv));
+            }
+            else{
+                println!("info depth {} nodes {} nps {} time {}", ply_depth, node_counter, node_counter as u64 * 1000 / elapsed_ms, elapsed_ms);
+            }
+        }
+        else{
+            println!("MATE SEARCHED TO {} nodes:{} best move: {} eval: {}", mate_depth, node_counter, get_move_string(best_mvel.mv), get_score_string(best_mvel.score));
+        }
+
+        if best_mvel.score > CHECKMATE_SCORE{
+            return best_mvel;
+        }
+    }
+
+    return MoveScorePair::new(0, 0);
+}
+
 pub fn get_pv_line(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64, u8>, transposition_table: &TranspositionTable, depth: u8, ply: u8, pv_line: &mut [u16; 32]){
     if depth == 0 || ply == 32{
         return 
@@ -1465,16 +2764,26 @@ pub fn get_pv_line(chess_board: &mut ChessBoard, game_tree: &mut HashMap<u64, u8
     
     let chess_board_repetition = add_to_game_tree(game_tree, chess_board.zobrist_hash);
 
+    if chess_board_repetition as usize >= REPETITION_COUNT_HASHES.len(){
+        remove_from_game_tree(game_tree, chess_board.zobrist_hash);
+        return;
+    }
+
     let true_hash = chess_board.zobrist_hash ^ REPETITION_COUNT_HASHES[chess_board_repetition as usize];
-    
-    let tt_entry = transposition_table.get(true_hash);
 
-    if tt_entry.hash == true_hash{
-        pv_line[ply as usize] = tt_entry.best_move;
+    let mut move_buffer = MoveBuffer::new();
+
+    get_moves(chess_board, &mut move_buffer);
+
+    // the entry could belong to another position with the same index bits, or have no move at all
+    let tt_move = transposition_table.get(true_hash).map(|tt_entry| tt_entry.best_move).filter(|mv| move_buffer.mv_arr[0..move_buffer.index].contains(mv));
+
+    if let Some(tt_move) = tt_move{
+        pv_line[ply as usize] = tt_move;
 
         let mut sub_board = chess_board.clone();
 
-        make_move(&mut sub_board, tt_entry.best_move);
+        make_move(&mut sub_board, tt_move);
         
         get_pv_line(&mut sub_board, game_tree, transposition_table, depth - 1, ply + 1, pv_line);
     }
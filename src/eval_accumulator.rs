@@ -0,0 +1,143 @@
+use std::sync::{Arc, OnceLock};
+
+use crate::board::*;
+use crate::eval_params::*;
+use crate::eval_score::*;
+use crate::evaluation::*;
+
+// material and piece square scores are kept inside the ChessBoard and updated by make_move
+// like the zobrist hash, so the evaluation doesnt have to loop over every bitboard
+
+// the scores make_move adds and removes, from the pieces own side (black squares are mirrored)
+// every board points to the tables of the eval params it was made with
+// after the params change the board needs new tables and update_eval_accumulators
+pub struct EvalAccumulatorTables{
+    piece_value_scores: [EvalScore; 12],
+    piece_square_scores: [[EvalScore; 64]; 12],
+}
+
+impl EvalAccumulatorTables{
+    pub const fn new(eval_params: &EvalParams) -> EvalAccumulatorTables{
+        let mut piece_value_scores = [ZERO_EVAL_SCORE; 12];
+        let mut piece_square_scores = [[ZERO_EVAL_SCORE; 64]; 12];
+
+        let mut piece_type = 0;
+
+        while piece_type < 12{
+            piece_value_scores[piece_type] = get_piece_value_score(eval_params, piece_type);
+
+            let mut square = 0;
+
+            while square < 64{
+                piece_square_scores[piece_type][square] = get_piece_square_score(eval_params, piece_type, square);
+                square += 1;
+            }
+
+            piece_type += 1;
+        }
+
+        return EvalAccumulatorTables{piece_value_scores, piece_square_scores};
+    }
+}
+
+// shared by every board that uses the default params
+static DEFAULT_EVAL_TABLES: OnceLock<Arc<EvalAccumulatorTables>> = OnceLock::new();
+
+pub fn get_default_eval_tables() -> Arc<EvalAccumulatorTables>{
+    return DEFAULT_EVAL_TABLES.get_or_init(|| Arc::new(EvalAccumulatorTables::new(&DEFAULT_EVAL_PARAMS))).clone();
+}
+
+const fn get_piece_value_score(eval_params: &EvalParams, piece_type: usize) -> EvalScore{
+    let local_piece_type = piece_type % 6;
+
+    // the king doesnt have a value
+    if local_piece_type == 5{
+        return ZERO_EVAL_SCORE;
+    }
+
+    return make_eval_score(eval_params.piece_values[local_piece_type], eval_params.piece_endgame_values[local_piece_type]);
+}
+
+const fn get_piece_square_score(eval_params: &EvalParams, piece_type: usize, square: usize) -> EvalScore{
+    let table_square = if piece_type < 6 { square } else { reverse_piece_square_index(square) };
+
+    let (table, endgame_table) = match piece_type % 6{
+        0 => (&eval_params.pawn_table, &eval_params.pawn_endgame_table),
+        1 => (&eval_params.bishop_table, &eval_params.bishop_endgame_table),
+        2 => (&eval_params.knight_table, &eval_params.knight_endgame_table),
+        3 => (&eval_params.rook_table, &eval_params.rook_endgame_table),
+        4 => (&eval_params.queen_table, &eval_params.queen_endgame_table),
+        _ => (&eval_params.king_table, &eval_params.king_endgame_table),
+    };
+
+    return make_eval_score(table[table_square], endgame_table[table_square]);
+}
+
+fn get_phase_weight(piece_type: usize) -> i16{
+    let local_piece_type = piece_type % 6;
+
+    // pawns and kings dont count
+    if local_piece_type == 5{
+        return 0;
+    }
+
+    return PIECE_ENDGAME_WEIGHTS[local_piece_type];
+}
+
+pub fn add_piece_eval_scores(chess_board: &mut ChessBoard, piece_type: usize, square: usize){
+    let color_index = piece_type / 6;
+
+    chess_board.material_scores[color_index] += chess_board.eval_tables.piece_value_scores[piece_type];
+    chess_board.piece_square_scores[color_index] += chess_board.eval_tables.piece_square_scores[piece_type][square];
+    chess_board.phase_material += get_phase_weight(piece_type);
+}
+
+pub fn remove_piece_eval_scores(chess_board: &mut ChessBoard, piece_type: usize, square: usize){
+    let color_index = piece_type / 6;
+
+    chess_board.material_scores[color_index] -= chess_board.eval_tables.piece_value_scores[piece_type];
+    chess_board.piece_square_scores[color_index] -= chess_board.eval_tables.piece_square_scores[piece_type][square];
+    chess_board.phase_material -= get_phase_weight(piece_type);
+}
+
+// material and phase stay the same
+pub fn move_piece_eval_scores(chess_board: &mut ChessBoard, piece_type: usize, from_square: usize, to_square: usize){
+    let color_index = piece_type / 6;
+
+    chess_board.piece_square_scores[color_index] -= chess_board.eval_tables.piece_square_scores[piece_type][from_square];
+    chess_board.piece_square_scores[color_index] += chess_board.eval_tables.piece_square_scores[piece_type][to_square];
+}
+
+// recomputes everything from the bitboards
+pub fn update_eval_accumulators(chess_board: &mut ChessBoard){
+    chess_board.material_scores = [ZERO_EVAL_SCORE; 2];
+    chess_board.piece_square_scores = [ZERO_EVAL_SCORE; 2];
+    chess_board.phase_material = 0;
+
+    for piece_type in 0..12{
+        let mut temp_bitboard: u64 = chess_board.piece_bitboards[piece_type];
+
+        while temp_bitboard != 0{
+            let square: usize = temp_bitboard.trailing_zeros() as usize;
+
+            add_piece_eval_scores(chess_board, piece_type, square);
+
+            temp_bitboard ^= 1 << square;
+        }
+    }
+}
+
+// compares the accumulators with the evaluation terms computed the slow way
+pub fn check_eval_accumulators(chess_board: &ChessBoard, eval_params: &EvalParams) -> bool{
+    for (color_index, board_color) in [true, false].into_iter().enumerate(){
+        let material_score = get_board_piece_value_score(chess_board, board_color, eval_params);
+
+        let piece_square_score = get_board_piece_square_score(chess_board, board_color, eval_params) + get_pawn_piece_square_score(chess_board, board_color, eval_params) + king_endgame_square_weight(chess_board, board_color, eval_params);
+
+        if chess_board.material_scores[color_index] != material_score || chess_board.piece_square_scores[color_index] != piece_square_score{
+            return false;
+        }
+    }
+
+    return chess_board.phase_material == get_phase_material(chess_board);
+}
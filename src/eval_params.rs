@@ -0,0 +1,347 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+
+use crate::evaluation::*;
+
+// every weight the evaluation uses, so parameter sets can be swapped without recompiling
+// terms with a middlegame and endgame value have an _endgame twin, single terms only count in one phase
+
+// File Layout (text):
+// one parameter per line, the name followed by its values
+// lines starting with # are comments
+// parameters that are left out keep their default value
+
+pub const DEFAULT_EVAL_FILE: &'static str = "eval_params.txt";
+
+#[derive(Clone)]
+pub struct EvalParams{
+    // pawn, bishop, knight, rook, queen
+    pub piece_values: [i16; 5],
+    pub piece_endgame_values: [i16; 5],
+
+    // square 0 is a8 from whites side, black uses the mirrored square
+    pub pawn_table: [i16; 64],
+    pub pawn_endgame_table: [i16; 64],
+    pub bishop_table: [i16; 64],
+    pub bishop_endgame_table: [i16; 64],
+    pub knight_table: [i16; 64],
+    pub knight_endgame_table: [i16; 64],
+    pub rook_table: [i16; 64],
+    pub rook_endgame_table: [i16; 64],
+    pub queen_table: [i16; 64],
+    pub queen_endgame_table: [i16; 64],
+    pub king_table: [i16; 64],
+    pub king_endgame_table: [i16; 64],
+
+    // endgame only
+    pub passed_pawn_table: [i16; 64],
+    pub bishop_knight_endgame_bias_table: [i16; 64],
+
+    // middlegame only
+    // by number of pawns next to the king
+    pub pawn_shield_penalty: [i16; 8],
+    pub king_open_file_penalty: i16,
+
+    // by number of attack units on the king zone
+    pub attack_unit_table: [i16; 100],
+    // bishop, knight, rook, queen
+    pub piece_attack_units: [i16; 4],
+
+    pub attack_square_bonus: i16,
+    pub center_attack_square_bonus: i16,
+
+    // endgame only
+    pub doubled_pawn_penalty: i16,
+    pub king_pawn_support_bonus: i16,
+    pub king_distance_bonus: i16,
+    pub unstoppable_pawn_bonus: i16,
+
+    // per pawn
+    pub isolated_pawn_penalty: i16,
+    pub isolated_pawn_endgame_penalty: i16,
+    pub backward_pawn_penalty: i16,
+    pub backward_pawn_endgame_penalty: i16,
+    pub connected_pawn_bonus: i16,
+    pub connected_pawn_endgame_bonus: i16,
+
+    // per square a piece can move to above MOBILITY_BASELINE, bishop, knight, rook, queen
+    pub mobility_bonus: [i16; 4],
+    pub mobility_endgame_bonus: [i16; 4],
+
+    pub bishop_pair_bonus: i16,
+    pub bishop_pair_endgame_bonus: i16,
+
+    // per rook
+    pub rook_open_file_bonus: i16,
+    pub rook_open_file_endgame_bonus: i16,
+    pub rook_semi_open_file_bonus: i16,
+    pub rook_semi_open_file_endgame_bonus: i16,
+    pub rook_seventh_rank_bonus: i16,
+    pub rook_seventh_rank_endgame_bonus: i16,
+
+    // per knight
+    pub knight_outpost_bonus: i16,
+    pub knight_outpost_endgame_bonus: i16,
+
+    // per enemy piece
+    pub pawn_threat_bonus: i16,
+    pub pawn_threat_endgame_bonus: i16,
+    pub hanging_piece_bonus: i16,
+    pub hanging_piece_endgame_bonus: i16,
+
+    // middlegame only
+    // per safe square behind the pawns
+    pub space_bonus: i16,
+}
+
+pub const DEFAULT_EVAL_PARAMS: EvalParams = EvalParams{
+    piece_values: [PIECE_TYPE_VALUES[0], PIECE_TYPE_VALUES[1], PIECE_TYPE_VALUES[2], PIECE_TYPE_VALUES[3], PIECE_TYPE_VALUES[4]],
+    piece_endgame_values: [PIECE_TYPE_VALUES[0], PIECE_TYPE_VALUES[1], PIECE_TYPE_VALUES[2], PIECE_TYPE_VALUES[3], PIECE_TYPE_VALUES[4]],
+
+    pawn_table: PAWN_PIECE_SQUARE_TABLE,
+    pawn_endgame_table: PAWN_PIECE_SQUARE_ENDGAME_TABLE,
+    bishop_table: BISHOP_PIECE_SQUARE_TABLE,
+    bishop_endgame_table: BISHOP_PIECE_SQUARE_TABLE,
+    knight_table: KNIGHT_PIECE_SQUARE_TABLE,
+    knight_endgame_table: KNIGHT_PIECE_SQUARE_TABLE,
+    rook_table: ROOK_PIECE_SQUARE_TABLE,
+    rook_endgame_table: ROOK_PIECE_SQUARE_TABLE,
+    queen_table: QUEEN_PIECE_SQUARE_TABLE,
+    queen_endgame_table: QUEEN_PIECE_SQUARE_TABLE,
+    king_table: KING_PIECE_SQUARE_TABLE,
+    king_endgame_table: KING_PIECE_SQUARE_ENDGAME_TABLE,
+    passed_pawn_table: PASS_PAWN_PIECE_SQUARE_TABLE,
+    bishop_knight_endgame_bias_table: BISHOP_KNIGHT_ENDGAME_BIAS_TABLE,
+
+    pawn_shield_penalty: PAWN_SHIELD_PENALTY,
+    king_open_file_penalty: 20,
+
+    attack_unit_table: ATTACK_UNIT_TABLE,
+    piece_attack_units: PIECE_ATTACK_UNIT,
+
+    attack_square_bonus: 2,
+    center_attack_square_bonus: 10,
+
+    doubled_pawn_penalty: 10,
+    king_pawn_support_bonus: 20,
+    king_distance_bonus: 10,
+    unstoppable_pawn_bonus: 300,
+
+    isolated_pawn_penalty: 10,
+    isolated_pawn_endgame_penalty: 15,
+    backward_pawn_penalty: 8,
+    backward_pawn_endgame_penalty: 10,
+    connected_pawn_bonus: 5,
+    connected_pawn_endgame_bonus: 10,
+
+    mobility_bonus: [4, 4, 2, 1],
+    mobility_endgame_bonus: [4, 4, 4, 2],
+
+    bishop_pair_bonus: 30,
+    bishop_pair_endgame_bonus: 50,
+
+    rook_open_file_bonus: 25,
+    rook_open_file_endgame_bonus: 10,
+    rook_semi_open_file_bonus: 12,
+    rook_semi_open_file_endgame_bonus: 5,
+    rook_seventh_rank_bonus: 20,
+    rook_seventh_rank_endgame_bonus: 30,
+
+    knight_outpost_bonus: 30,
+    knight_outpost_endgame_bonus: 15,
+
+    pawn_threat_bonus: 50,
+    pawn_threat_endgame_bonus: 40,
+    hanging_piece_bonus: 30,
+    hanging_piece_endgame_bonus: 20,
+
+    space_bonus: 3,
+};
+
+impl EvalParams{
+    pub fn new() -> EvalParams{
+        return DEFAULT_EVAL_PARAMS;
+    }
+
+    // every parameter with the name it is saved under
+    pub fn get_params(&self) -> Vec<(&'static str, &[i16])>{
+        return vec![
+            ("piece_values", &self.piece_values),
+            ("piece_endgame_values", &self.piece_endgame_values),
+            ("pawn_table", &self.pawn_table),
+            ("pawn_endgame_table", &self.pawn_endgame_table),
+            ("bishop_table", &self.bishop_table),
+            ("bishop_endgame_table", &self.bishop_endgame_table),
+            ("knight_table", &self.knight_table),
+            ("knight_endgame_table", &self.knight_endgame_table),
+            ("rook_table", &self.rook_table),
+            ("rook_endgame_table", &self.rook_endgame_table),
+            ("queen_table", &self.queen_table),
+            ("queen_endgame_table", &self.queen_endgame_table),
+            ("king_table", &self.king_table),
+            ("king_endgame_table", &self.king_endgame_table),
+            ("passed_pawn_table", &self.passed_pawn_table),
+            ("bishop_knight_endgame_bias_table", &self.bishop_knight_endgame_bias_table),
+            ("pawn_shield_penalty", &self.pawn_shield_penalty),
+            ("king_open_file_penalty", std::slice::from_ref(&self.king_open_file_penalty)),
+            ("attack_unit_table", &self.attack_unit_table),
+            ("piece_attack_units", &self.piece_attack_units),
+            ("attack_square_bonus", std::slice::from_ref(&self.attack_square_bonus)),
+            ("center_attack_square_bonus", std::slice::from_ref(&self.center_attack_square_bonus)),
+            ("doubled_pawn_penalty", std::slice::from_ref(&self.doubled_pawn_penalty)),
+            ("king_pawn_support_bonus", std::slice::from_ref(&self.king_pawn_support_bonus)),
+            ("king_distance_bonus", std::slice::from_ref(&self.king_distance_bonus)),
+            ("unstoppable_pawn_bonus", std::slice::from_ref(&self.unstoppable_pawn_bonus)),
+            ("isolated_pawn_penalty", std::slice::from_ref(&self.isolated_pawn_penalty)),
+            ("isolated_pawn_endgame_penalty", std::slice::from_ref(&self.isolated_pawn_endgame_penalty)),
+            ("backward_pawn_penalty", std::slice::from_ref(&self.backward_pawn_penalty)),
+            ("backward_pawn_endgame_penalty", std::slice::from_ref(&self.backward_pawn_endgame_penalty)),
+            ("connected_pawn_bonus", std::slice::from_ref(&self.connected_pawn_bonus)),
+            ("connected_pawn_endgame_bonus", std::slice::from_ref(&self.connected_pawn_endgame_bonus)),
+            ("mobility_bonus", &self.mobility_bonus),
+            ("mobility_endgame_bonus", &self.mobility_endgame_bonus),
+            ("bishop_pair_bonus", std::slice::from_ref(&self.bishop_pair_bonus)),
+            ("bishop_pair_endgame_bonus", std::slice::from_ref(&self.bishop_pair_endgame_bonus)),
+            ("rook_open_file_bonus", std::slice::from_ref(&self.rook_open_file_bonus)),
+            ("rook_open_file_endgame_bonus", std::slice::from_ref(&self.rook_open_file_endgame_bonus)),
+            ("rook_semi_open_file_bonus", std::slice::from_ref(&self.rook_semi_open_file_bonus)),
+            ("rook_semi_open_file_endgame_bonus", std::slice::from_ref(&self.rook_semi_open_file_endgame_bonus)),
+            ("rook_seventh_rank_bonus", std::slice::from_ref(&self.rook_seventh_rank_bonus)),
+            ("rook_seventh_rank_endgame_bonus", std::slice::from_ref(&self.rook_seventh_rank_endgame_bonus)),
+            ("knight_outpost_bonus", std::slice::from_ref(&self.knight_outpost_bonus)),
+            ("knight_outpost_endgame_bonus", std::slice::from_ref(&self.knight_outpost_endgame_bonus)),
+            ("pawn_threat_bonus", std::slice::from_ref(&self.pawn_threat_bonus)),
+            ("pawn_threat_endgame_bonus", std::slice::from_ref(&self.pawn_threat_endgame_bonus)),
+            ("hanging_piece_bonus", std::slice::from_ref(&self.hanging_piece_bonus)),
+            ("hanging_piece_endgame_bonus", std::slice::from_ref(&self.hanging_piece_endgame_bonus)),
+            ("space_bonus", std::slice::from_ref(&self.space_bonus)),
+        ];
+    }
+
+    // same order as get_params
+    pub fn get_params_mut(&mut self) -> Vec<(&'static str, &mut [i16])>{
+        return vec![
+            ("piece_values", &mut self.piece_values),
+            ("piece_endgame_values", &mut self.piece_endgame_values),
+            ("pawn_table", &mut self.pawn_table),
+            ("pawn_endgame_table", &mut self.pawn_endgame_table),
+            ("bishop_table", &mut self.bishop_table),
+            ("bishop_endgame_table", &mut self.bishop_endgame_table),
+            ("knight_table", &mut self.knight_table),
+            ("knight_endgame_table", &mut self.knight_endgame_table),
+            ("rook_table", &mut self.rook_table),
+            ("rook_endgame_table", &mut self.rook_endgame_table),
+            ("queen_table", &mut self.queen_table),
+            ("queen_endgame_table", &mut self.queen_endgame_table),
+            ("king_table", &mut self.king_table),
+            ("king_endgame_table", &mut self.king_endgame_table),
+            ("passed_pawn_table", &mut self.passed_pawn_table),
+            ("bishop_knight_endgame_bias_table", &mut self.bishop_knight_endgame_bias_table),
+            ("pawn_shield_penalty", &mut self.pawn_shield_penalty),
+            ("king_open_file_penalty", std::slice::from_mut(&mut self.king_open_file_penalty)),
+            ("attack_unit_table", &mut self.attack_unit_table),
+            ("piece_attack_units", &mut self.piece_attack_units),
+            ("attack_square_bonus", std::slice::from_mut(&mut self.attack_square_bonus)),
+            ("center_attack_square_bonus", std::slice::from_mut(&mut self.center_attack_square_bonus)),
+            ("doubled_pawn_penalty", std::slice::from_mut(&mut self.doubled_pawn_penalty)),
+            ("king_pawn_support_bonus", std::slice::from_mut(&mut self.king_pawn_support_bonus)),
+            ("king_distance_bonus", std::slice::from_mut(&mut self.king_distance_bonus)),
+            ("unstoppable_pawn_bonus", std::slice::from_mut(&mut self.unstoppable_pawn_bonus)),
+            ("isolated_pawn_penalty", std::slice::from_mut(&mut self.isolated_pawn_penalty)),
+            ("isolated_pawn_endgame_penalty", std::slice::from_mut(&mut self.isolated_pawn_endgame_penalty)),
+            ("backward_pawn_penalty", std::slice::from_mut(&mut self.backward_pawn_penalty)),
+            ("backward_pawn_endgame_penalty", std::slice::from_mut(&mut self.backward_pawn_endgame_penalty)),
+            ("connected_pawn_bonus", std::slice::from_mut(&mut self.connected_pawn_bonus)),
+            ("connected_pawn_endgame_bonus", std::slice::from_mut(&mut self.connected_pawn_endgame_bonus)),
+            ("mobility_bonus", &mut self.mobility_bonus),
+            ("mobility_endgame_bonus", &mut self.mobility_endgame_bonus),
+            ("bishop_pair_bonus", std::slice::from_mut(&mut self.bishop_pair_bonus)),
+            ("bishop_pair_endgame_bonus", std::slice::from_mut(&mut self.bishop_pair_endgame_bonus)),
+            ("rook_open_file_bonus", std::slice::from_mut(&mut self.rook_open_file_bonus)),
+            ("rook_open_file_endgame_bonus", std::slice::from_mut(&mut self.rook_open_file_endgame_bonus)),
+            ("rook_semi_open_file_bonus", std::slice::from_mut(&mut self.rook_semi_open_file_bonus)),
+            ("rook_semi_open_file_endgame_bonus", std::slice::from_mut(&mut self.rook_semi_open_file_endgame_bonus)),
+            ("rook_seventh_rank_bonus", std::slice::from_mut(&mut self.rook_seventh_rank_bonus)),
+            ("rook_seventh_rank_endgame_bonus", std::slice::from_mut(&mut self.rook_seventh_rank_endgame_bonus)),
+            ("knight_outpost_bonus", std::slice::from_mut(&mut self.knight_outpost_bonus)),
+            ("knight_outpost_endgame_bonus", std::slice::from_mut(&mut self.knight_outpost_endgame_bonus)),
+            ("pawn_threat_bonus", std::slice::from_mut(&mut self.pawn_threat_bonus)),
+            ("pawn_threat_endgame_bonus", std::slice::from_mut(&mut self.pawn_threat_endgame_bonus)),
+            ("hanging_piece_bonus", std::slice::from_mut(&mut self.hanging_piece_bonus)),
+            ("hanging_piece_endgame_bonus", std::slice::from_mut(&mut self.hanging_piece_endgame_bonus)),
+            ("space_bonus", std::slice::from_mut(&mut self.space_bonus)),
+        ];
+    }
+}
+
+fn invalid_data(message: String) -> io::Error{
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub fn save_eval_params(file_path: &str, eval_params: &EvalParams) -> io::Result<()>{
+    let mut writer = BufWriter::new(File::create(file_path)?);
+
+    writeln!(writer, "# chess_rust evaluation parameters")?;
+
+    for (name, values) in eval_params.get_params(){
+        let value_strings: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+
+        writeln!(writer, "{} {}", name, value_strings.join(" "))?;
+    }
+
+    writer.flush()?;
+
+    return Ok(());
+}
+
+// nothing gets changed unless the whole file is valid
+pub fn load_eval_params(file_path: &str, eval_params: &mut EvalParams) -> io::Result<()>{
+    let reader = BufReader::new(File::open(file_path)?);
+
+    let mut loaded_params = eval_params.clone();
+
+    for (line_i, line) in reader.lines().enumerate(){
+        let line = line?;
+        let line = line.trim();
+
+        if line == "" || line.starts_with('#'){
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let name = tokens.next().unwrap();
+
+        let mut values: Vec<i16> = Vec::new();
+
+        for token in tokens{
+            match token.parse::<i16>(){
+                Ok(value) => values.push(value),
+                Err(_) => return Err(invalid_data(format!("line {}: {} is not a number", line_i + 1, token))),
+            }
+        }
+
+        let mut found = false;
+
+        for (param_name, param_values) in loaded_params.get_params_mut(){
+            if param_name != name{
+                continue;
+            }
+
+            if param_values.len() != values.len(){
+                return Err(invalid_data(format!("line {}: {} needs {} values (got {})", line_i + 1, name, param_values.len(), values.len())));
+            }
+
+            param_values.copy_from_slice(&values);
+            found = true;
+            break;
+        }
+
+        if !found{
+            return Err(invalid_data(format!("line {}: unknown parameter {}", line_i + 1, name)));
+        }
+    }
+
+    *eval_params = loaded_params;
+
+    return Ok(());
+}
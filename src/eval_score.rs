@@ -0,0 +1,35 @@
+// middlegame and endgame score packed into one integer
+// the endgame half sits in the upper 16 bits, so two scores can be added, subtracted
+// or multiplied by a whole number in one go as long as both halves stay inside i16
+
+pub type EvalScore = i32;
+
+pub const ZERO_EVAL_SCORE: EvalScore = 0;
+
+pub const fn make_eval_score(mg_score: i16, eg_score: i16) -> EvalScore{
+    return ((eg_score as i32) << 16).wrapping_add(mg_score as i32);
+}
+
+pub fn get_mg_score(eval_score: EvalScore) -> i16{
+    return eval_score as u16 as i16;
+}
+
+// the lower half is signed, a negative middlegame score borrows one from the upper half
+pub fn get_eg_score(eval_score: EvalScore) -> i16{
+    return ((eval_score.wrapping_add(0x8000) as u32) >> 16) as u16 as i16;
+}
+
+// endgame_weight goes from 0 (all pieces on the board) to endgame_weight_max (only pawns and kings left)
+pub fn taper_eval_score(eval_score: EvalScore, endgame_weight: i16, endgame_weight_max: i16) -> i16{
+    let mg_score = get_mg_score(eval_score) as i32;
+    let eg_score = get_eg_score(eval_score) as i32;
+
+    return ((mg_score * (endgame_weight_max - endgame_weight) as i32 + eg_score * endgame_weight as i32) / endgame_weight_max as i32) as i16;
+}
+
+// only the endgame half, the middlegame half stays as it is
+pub fn scale_eg_score(eval_score: EvalScore, scale_factor: i16, scale_factor_max: i16) -> EvalScore{
+    let eg_score = get_eg_score(eval_score) as i32 * scale_factor as i32 / scale_factor_max as i32;
+
+    return make_eval_score(get_mg_score(eval_score), eg_score as i16);
+}
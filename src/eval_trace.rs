@@ -0,0 +1,76 @@
+use crate::eval_score::*;
+use crate::evaluation::ENDGAME_WEIGHT_MAX;
+use crate::endgame::SCALE_FACTOR_NORMAL;
+
+// breakdown of a single evaluation, filled in by get_eval_trace
+// only used for show eval, the search goes through get_board_score which skips all of this
+
+pub struct EvalTraceTerm{
+    pub name: &'static str,
+    // each from the sides own point of view, the term adds white - black to the score
+    pub white_score: EvalScore,
+    pub black_score: EvalScore,
+}
+
+pub struct EvalTrace{
+    pub terms: Vec<EvalTraceTerm>,
+
+    // set when a specialised endgame evaluator replaced the terms
+    pub endgame_name: Option<&'static str>,
+    // applied to the endgame half of the summed terms
+    pub scale_factor: i16,
+
+    pub endgame_weight: i16,
+
+    // white relative, before and after blending
+    pub score: EvalScore,
+    pub tapered_score: i16,
+    pub board_color: bool,
+}
+
+impl EvalTrace{
+    pub fn new() -> EvalTrace{
+        EvalTrace{
+            terms: Vec::new(),
+
+            endgame_name: None,
+            scale_factor: SCALE_FACTOR_NORMAL,
+
+            endgame_weight: 0,
+
+            score: ZERO_EVAL_SCORE,
+            tapered_score: 0,
+            board_color: true,
+        }
+    }
+
+    pub fn add_term(&mut self, name: &'static str, white_score: EvalScore, black_score: EvalScore){
+        self.terms.push(EvalTraceTerm{name, white_score, black_score});
+    }
+
+    pub fn show(&self){
+        if let Some(endgame_name) = self.endgame_name{
+            println!("endgame evaluator: {}", endgame_name);
+        }
+        else{
+            println!("{:<20} {:>6} {:>6} {:>6} {:>6} {:>7}", "term", "w mg", "w eg", "b mg", "b eg", "total");
+
+            for term in self.terms.iter(){
+                let total = taper_eval_score(term.white_score - term.black_score, self.endgame_weight, ENDGAME_WEIGHT_MAX);
+
+                println!("{:<20} {:>6} {:>6} {:>6} {:>6} {:>7}", term.name, get_mg_score(term.white_score), get_eg_score(term.white_score), get_mg_score(term.black_score), get_eg_score(term.black_score), total);
+            }
+        }
+
+        // the term totals are rounded on their own so they can be a bit off from the blended total
+        println!("endgame weight: {}/{}", self.endgame_weight, ENDGAME_WEIGHT_MAX);
+
+        // the term totals above are unscaled
+        if self.scale_factor != SCALE_FACTOR_NORMAL{
+            println!("endgame scale factor: {}/{}", self.scale_factor, SCALE_FACTOR_NORMAL);
+        }
+
+        println!("total (white): mg {} eg {} tapered {}", get_mg_score(self.score), get_eg_score(self.score), self.tapered_score);
+        println!("total (rel): {}", if self.board_color { self.tapered_score } else { -self.tapered_score });
+    }
+}
@@ -3,6 +3,12 @@ use crate::functions::*;
 use crate::board::*;
 use crate::game_board::*;
 use crate::app_settings::MOVE_LIMIT_MAX;
+use crate::eval_params::*;
+use crate::eval_trace::*;
+use crate::eval_score::*;
+use crate::eval_accumulator::*;
+use crate::pawn_hash_table::*;
+use crate::endgame::*;
 
 // 0 - still going
 // 1 - white checkmate
@@ -44,9 +50,22 @@ pub fn get_gamestate(game_board: &mut GameChessBoard) -> u8{
         return 3;
     }
 
-    let total_bishop_num = chess_board.piece_bitboards[1].count_ones() + chess_board.piece_bitboards[7].count_ones();
-    let total_knight_num = chess_board.piece_bitboards[2].count_ones() + chess_board.piece_bitboards[8].count_ones();
+    if chess_board.halfmove_clock >= FIFTY_MOVE_HALFMOVES{
+        return 3;
+    }
+
+    if is_insufficient_material(chess_board){
+        return 3;
+    }
+
+    return 0;
+}
 
+// 50 moves for each side
+pub const FIFTY_MOVE_HALFMOVES: u8 = 100;
+
+// neither side can ever checkmate
+pub fn is_insufficient_material(chess_board: &ChessBoard) -> bool{
     // there is a pawn or queen or rooks on the board
     if chess_board.piece_bitboards[0] | 
     chess_board.piece_bitboards[6] | 
@@ -54,20 +73,17 @@ pub fn get_gamestate(game_board: &mut GameChessBoard) -> u8{
     chess_board.piece_bitboards[10] |
     chess_board.piece_bitboards[3] | 
     chess_board.piece_bitboards[9] != 0{
-        return 0;
+        return false;
     }
 
-    println!("{}", total_bishop_num);
+    let total_bishop_num = chess_board.piece_bitboards[1].count_ones() + chess_board.piece_bitboards[7].count_ones();
+    let total_knight_num = chess_board.piece_bitboards[2].count_ones() + chess_board.piece_bitboards[8].count_ones();
 
     // there are less than 2 bishops or less than 3 knights or less than 1 knight + bishop
-    if total_bishop_num < 2 && total_knight_num < 3 && total_bishop_num + total_knight_num < 2{
-        return 3;
-    }
-
-    return 0;
+    return total_bishop_num < 2 && total_knight_num < 3 && total_bishop_num + total_knight_num < 2;
 }
 
-const PIECE_TYPE_VALUES : [i16; 12] = [
+pub const PIECE_TYPE_VALUES : [i16; 12] = [
     100,
     300,
     300,
@@ -101,7 +117,7 @@ const ENGAME_PIECE_TYPE_VALUES: [i16; 12] = [
 ];
 
 // HIGHLY INSPIRED by Sebastian Lagues king square table
-const KING_PIECE_SQUARE_TABLE: [i16; 64] = [
+pub const KING_PIECE_SQUARE_TABLE: [i16; 64] = [
     -80,  -80,  -90,  -90,  -90,  -90,  -80,  -80,  
     -80,  -80,  -80,  -90,  -90,  -80,  -80,  -80,  
     -90,  -50,  -50,  -90,  -90,  -20,  -50,  -90,  
@@ -112,7 +128,7 @@ const KING_PIECE_SQUARE_TABLE: [i16; 64] = [
      10,   20,   20,    0,    0,   20,   30,   20,
 ];
 
-const KING_PIECE_SQUARE_ENDGAME_TABLE: [i16; 64] = [
+pub const KING_PIECE_SQUARE_ENDGAME_TABLE: [i16; 64] = [
     -95,  -95,  -90,  -90,  -90,  -90,  -95,  -95,  
     -95,  -50,  -50,  -50,  -50,  -50,  -50,  -95,  
     -90,  -50,  -20,  -20,  -20,  -20,  -50,  -90,  
@@ -123,7 +139,7 @@ const KING_PIECE_SQUARE_ENDGAME_TABLE: [i16; 64] = [
     -95,  -95,  -90,  -90,  -90,  -90,  -95,  -95,
 ];
 
-const BISHOP_KNIGHT_ENDGAME_BIAS_TABLE: [i16; 64] = [
+pub const BISHOP_KNIGHT_ENDGAME_BIAS_TABLE: [i16; 64] = [
     -150, -100, -90,-70, 70, 90, 100, 150,
     -100,   0,  0 ,   0,  0,  0,  0, 100,
     -90,   0,  0 ,   0,  0,  0,  0, 90,
@@ -135,7 +151,7 @@ const BISHOP_KNIGHT_ENDGAME_BIAS_TABLE: [i16; 64] = [
 ];
 
 // HIGHLY HIGHLY INSPIRED (copied) from Sebastian Lagues tables
-const PAWN_PIECE_SQUARE_TABLE : [i16; 64] = [
+pub const PAWN_PIECE_SQUARE_TABLE : [i16; 64] = [
     0,   0,   0,   0,   0,   0,   0,   0,
    50,  50,  50,  50,  50,  50,  50,  50,
    10,  10,  20,  30,  30,  20,  10,  10,
@@ -146,7 +162,7 @@ const PAWN_PIECE_SQUARE_TABLE : [i16; 64] = [
     0,   0,   0,   0,   0,   0,   0,   0
 ];
 
-const PAWN_PIECE_SQUARE_ENDGAME_TABLE : [i16; 64] = [
+pub const PAWN_PIECE_SQUARE_ENDGAME_TABLE : [i16; 64] = [
     0,   0,   0,   0,   0,   0,   0,   0,
    80,  80,  80,  80,  80,  80,  80,  80,
    50,  50,  50,  50,  50,  50,  50,  50,
@@ -157,7 +173,7 @@ const PAWN_PIECE_SQUARE_ENDGAME_TABLE : [i16; 64] = [
     0,   0,   0,   0,   0,   0,   0,   0
 ];
 
-const KNIGHT_PIECE_SQUARE_TABLE : [i16; 64] = [
+pub const KNIGHT_PIECE_SQUARE_TABLE : [i16; 64] = [
     -50,-40,-30,-30,-30,-30,-40,-50,
     -40,-20,  0,  0,  0,  0,-20,-40,
     -30,  0, 10, 15, 15, 10,  0,-30,
@@ -168,7 +184,7 @@ const KNIGHT_PIECE_SQUARE_TABLE : [i16; 64] = [
     -50,-40,-30,-30,-30,-30,-40,-50,
 ];
 
-const BISHOP_PIECE_SQUARE_TABLE : [i16; 64] = [
+pub const BISHOP_PIECE_SQUARE_TABLE : [i16; 64] = [
     -20,-10,-10,-10,-10,-10,-10,-20,
     -10,  0,  0,  0,  0,  0,  0,-10,
     -10,  0,  5, 10, 10,  5,  0,-10,
@@ -179,7 +195,7 @@ const BISHOP_PIECE_SQUARE_TABLE : [i16; 64] = [
     -20,-10,-10,-10,-10,-10,-10,-20,
 ];
 
-const ROOK_PIECE_SQUARE_TABLE: [i16; 64] = [
+pub const ROOK_PIECE_SQUARE_TABLE: [i16; 64] = [
     0,  0,  0,  0,  0,  0,  0,  0,
     5, 10, 10, 10, 10, 10, 10,  5,
     -5,  0,  0,  0,  0,  0,  0, -5,
@@ -189,7 +205,7 @@ const ROOK_PIECE_SQUARE_TABLE: [i16; 64] = [
     -5,  0,  0,  0,  0,  0,  0, -5,
     0,  0,  0,  5,  5,  0,  0,  0
 ];
-const QUEEN_PIECE_SQUARE_TABLE : [i16; 64] =  [
+pub const QUEEN_PIECE_SQUARE_TABLE : [i16; 64] =  [
     -20,-10,-10, -5, -5,-10,-10,-20,
     -10,  0,  0,  0,  0,  0,  0,-10,
     -10,  0,  5,  5,  5,  5,  0,-10,
@@ -201,7 +217,7 @@ const QUEEN_PIECE_SQUARE_TABLE : [i16; 64] =  [
 ];
 
 // heavily inspired from Blunder Engine
-const PASS_PAWN_PIECE_SQUARE_TABLE : [i16; 64] = [
+pub const PASS_PAWN_PIECE_SQUARE_TABLE : [i16; 64] = [
 	0, 0, 0, 0, 0, 0, 0, 0,
 	77, 74, 63, 53, 59, 60, 72, 77,
 	91, 83, 66, 40, 30, 61, 67, 84,
@@ -219,13 +235,6 @@ pub const KING_DIAG_VULNERABLE_MASK : [u64; 64] = GET_KING_DIAG_VULNERABLE_MASK(
 
 const IMPORTANT_ATTACK_SQUARES_MASK: u64 = 0x3C7E7E3C0000;
 
-const PIECE_SQUARE_TABLE_REF : [&[i16; 64]; 4] = [
-    &BISHOP_PIECE_SQUARE_TABLE,
-    &KNIGHT_PIECE_SQUARE_TABLE,
-    &ROOK_PIECE_SQUARE_TABLE,
-    &QUEEN_PIECE_SQUARE_TABLE
-];
-
 const fn GET_KING_DANGER_SQUARES_MASK() -> [u64; 64]{
     let mut bb_array = [0u64; 64];
     
@@ -325,7 +334,7 @@ const fn GET_KING_DIAG_VULNERABLE_MASK() -> [u64; 64]{
     return bb_array;
 }
 
-pub fn reverse_piece_square_index(index: usize) -> usize{
+pub const fn reverse_piece_square_index(index: usize) -> usize{
     let y = index / 8;
     let x = index % 8;
 
@@ -335,9 +344,16 @@ pub fn reverse_piece_square_index(index: usize) -> usize{
 
 pub const PIECE_ENDGAME_WEIGHTS : [i16; 5] = [0, 1, 1, 2, 4];
 
-// 1.0 when highest, 0.0 at lowest
-pub fn get_endgame_weight(board: &ChessBoard) -> f32{
-    let mut phase : i16 = 12;
+pub const ENDGAME_WEIGHT_MAX: i16 = 12;
+
+// game phase, 0 with every piece on the board, ENDGAME_WEIGHT_MAX with only pawns and kings left
+pub fn get_endgame_weight(board: &ChessBoard) -> i16{
+    return clamp_int(ENDGAME_WEIGHT_MAX - board.phase_material, 0, ENDGAME_WEIGHT_MAX);
+}
+
+// PIECE_ENDGAME_WEIGHTS of every piece on the board, make_move keeps board.phase_material at this
+pub fn get_phase_material(board: &ChessBoard) -> i16{
+    let mut phase_material : i16 = 0;
     
     for piece_bitboard_i in 0..12{
         let piece_type: usize = piece_bitboard_i % 6;
@@ -347,92 +363,78 @@ pub fn get_endgame_weight(board: &ChessBoard) -> f32{
             continue;
         }
 
-        phase -= PIECE_ENDGAME_WEIGHTS[piece_type] * board.piece_bitboards[piece_bitboard_i].count_ones() as i16;
+        phase_material += PIECE_ENDGAME_WEIGHTS[piece_type] * board.piece_bitboards[piece_bitboard_i].count_ones() as i16;
     }
 
-    return clamp_int(phase, 0, 12) as f32 / 12.0;
+    return phase_material;
 }
 
-pub fn king_endgame_square_weight(board: &ChessBoard, endgame_weight: f32) -> i16{
-    let mut score: i16 = 0;
-
-    let white_king_square : usize = board.piece_bitboards[5].trailing_zeros() as usize;
-    let black_king_square : usize = board.piece_bitboards[11].trailing_zeros() as usize;
-
-    // disregard engame weight if its too low
-    if endgame_weight > 0.3{
-        let reversed_black = reverse_piece_square_index(black_king_square);
+pub fn king_endgame_square_weight(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let king_square: usize;
 
-        score += lerp_int(KING_PIECE_SQUARE_TABLE[white_king_square], KING_PIECE_SQUARE_ENDGAME_TABLE[white_king_square], endgame_weight);
-        score -= lerp_int(KING_PIECE_SQUARE_TABLE[reversed_black], KING_PIECE_SQUARE_ENDGAME_TABLE[reversed_black], endgame_weight);
+    if board_color{
+        king_square = board.piece_bitboards[5].trailing_zeros() as usize;
     }
     else{
-        score += KING_PIECE_SQUARE_TABLE[white_king_square];
-        score -= KING_PIECE_SQUARE_TABLE[reverse_piece_square_index(black_king_square)];   
+        king_square = reverse_piece_square_index(board.piece_bitboards[11].trailing_zeros() as usize);
     }
 
-    return score;
+    return make_eval_score(eval_params.king_table[king_square], eval_params.king_endgame_table[king_square]);
 }
 
-pub fn king_distance_weight(board: &ChessBoard, endgame_weight: f32) -> i16{
-    if endgame_weight < 0.3{
-        return 0;
+// only the side to move gets the bonus
+pub fn king_distance_weight(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    if board.board_color != board_color{
+        return ZERO_EVAL_SCORE;
     }
 
     let white_king_square : i16 = board.piece_bitboards[5].trailing_zeros() as i16;
     let black_king_square : i16 = board.piece_bitboards[11].trailing_zeros() as i16;
 
-    let distance : f32 = get_manhattan_distance(white_king_square, black_king_square) as f32;
-
-    let weight: f32 = (12.0 - distance) * endgame_weight * 10.0;
+    let distance : i16 = get_manhattan_distance(white_king_square, black_king_square) as i16;
 
-    if board.board_color{
-        return weight as i16;
-    }
-    else{
-        return -(weight as i16);
-    }
+    return make_eval_score(0, (12 - distance) * eval_params.king_distance_bonus);
 }
 
 // eval based on piece value
-pub fn get_board_piece_value_score(board: &ChessBoard) -> i16{
-    let mut score: i16 = 0;
+pub fn get_board_piece_value_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let mut score: EvalScore = ZERO_EVAL_SCORE;
+
+    let piece_offset: usize = if board_color { 0 } else { 6 };
+
     // skip the king
     for i in 0..5{
-        score += (board.piece_bitboards[i].count_ones() as i16) * PIECE_TYPE_VALUES[i];
-    }
-
-    for i in 6..11{
-        score += (board.piece_bitboards[i].count_ones() as i16) * PIECE_TYPE_VALUES[i];
+        score += (board.piece_bitboards[i + piece_offset].count_ones() as EvalScore) * make_eval_score(eval_params.piece_values[i], eval_params.piece_endgame_values[i]);
     }
     
     return score;
 }
 
-// this only considers non pawns and kings (since they have endgame tables)
-pub fn get_board_piece_square_score(board: &ChessBoard) -> i16{
-    let mut score: i16 = 0;
+// this only considers non pawns and kings (they are scored seperately)
+pub fn get_board_piece_square_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let mut score: EvalScore = ZERO_EVAL_SCORE;
+
+    let piece_square_tables: [(&[i16; 64], &[i16; 64]); 4] = [
+        (&eval_params.bishop_table, &eval_params.bishop_endgame_table),
+        (&eval_params.knight_table, &eval_params.knight_endgame_table),
+        (&eval_params.rook_table, &eval_params.rook_endgame_table),
+        (&eval_params.queen_table, &eval_params.queen_endgame_table),
+    ];
+
+    let piece_offset: usize = if board_color { 0 } else { 6 };
+
     // bishop, knight, rook, queen
     for piece_type in 1..5{
-        let mut temp_bitboard: u64 = board.piece_bitboards[piece_type];
-        let piece_square_table: &[i16; 64] = PIECE_SQUARE_TABLE_REF[piece_type - 1];
+        let mut temp_bitboard: u64 = board.piece_bitboards[piece_type + piece_offset];
+        let (piece_square_table, piece_square_endgame_table) = piece_square_tables[piece_type - 1];
 
         while temp_bitboard != 0{
             let piece_square: usize = temp_bitboard.trailing_zeros() as usize;
 
-            score += piece_square_table[piece_square];
-
-            temp_bitboard ^= 1 << piece_square;
-        }
-    }
+            let table_square = if board_color { piece_square } else { reverse_piece_square_index(piece_square) };
 
-    for piece_type in 7..11{
-        let mut temp_bitboard: u64 = board.piece_bitboards[piece_type];
-        let piece_square_table: &[i16; 64] = PIECE_SQUARE_TABLE_REF[piece_type - 7];
+            score += make_eval_score(piece_square_table[table_square], piece_square_endgame_table[table_square]);
 
-        while temp_bitboard != 0{
-            let piece_square: usize = temp_bitboard.trailing_zeros() as usize;
-            score -= piece_square_table[reverse_piece_square_index(piece_square)];
             temp_bitboard ^= 1 << piece_square;
         }
     }
@@ -440,38 +442,19 @@ pub fn get_board_piece_square_score(board: &ChessBoard) -> i16{
     return score;
 }
 
-pub fn get_pawn_piece_square_score(board: &ChessBoard, endgame_weight: f32) -> i16{
-    let mut score: i16 = 0;
-
-    let mut white_pawn_bitboard : u64 = board.piece_bitboards[0];
-    let mut black_pawn_bitboard : u64 = board.piece_bitboards[6];
-
-    while white_pawn_bitboard != 0{
-        let pawn_square: usize = white_pawn_bitboard.trailing_zeros() as usize;
-        
-        if endgame_weight > 0.3{
-            score += lerp_int(PAWN_PIECE_SQUARE_TABLE[pawn_square], PAWN_PIECE_SQUARE_ENDGAME_TABLE[pawn_square], endgame_weight);
-        }
-        else{
-            score += PAWN_PIECE_SQUARE_TABLE[pawn_square];
-        }
+pub fn get_pawn_piece_square_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let mut score: EvalScore = ZERO_EVAL_SCORE;
 
-        white_pawn_bitboard ^= 1 << pawn_square;
-    }
+    let mut pawn_bitboard : u64 = if board_color { board.piece_bitboards[0] } else { board.piece_bitboards[6] };
 
-    while black_pawn_bitboard != 0{
-        let pawn_square: usize = black_pawn_bitboard.trailing_zeros() as usize;
+    while pawn_bitboard != 0{
+        let pawn_square: usize = pawn_bitboard.trailing_zeros() as usize;
 
-        let reversed_pawn_square = reverse_piece_square_index(pawn_square);
+        let table_square = if board_color { pawn_square } else { reverse_piece_square_index(pawn_square) };
         
-        if endgame_weight > 0.3{
-            score -= lerp_int(PAWN_PIECE_SQUARE_TABLE[reversed_pawn_square], PAWN_PIECE_SQUARE_ENDGAME_TABLE[reversed_pawn_square], endgame_weight);
-        }
-        else{
-            score -= PAWN_PIECE_SQUARE_TABLE[reversed_pawn_square];
-        }
+        score += make_eval_score(eval_params.pawn_table[table_square], eval_params.pawn_endgame_table[table_square]);
 
-        black_pawn_bitboard ^= 1 << pawn_square;
+        pawn_bitboard ^= 1 << pawn_square;
     }
 
     return score;
@@ -483,27 +466,6 @@ const HORIZONTAL_SLICE_BITBOARD : u64 = 0xFF00000000000000;
 const VERTICLE_SLICE_BITBOARD : u64 = 0x8080808080808080;
 const HALF_SLICE_BITBOARD: u64 = (!0)<<32;
 
-pub fn doubled_pawn_score(board: &ChessBoard, endgame_weight: f32) -> i16{
-    let mut score : f32 = 0.0;
-
-    let white_pawn_bitboard = board.piece_bitboards[0];
-    let black_pawn_bitboard = board.piece_bitboards[6];
-
-    for x in 0..8{
-        // white doubled pawns
-        if (white_pawn_bitboard & (VERTICLE_SLICE_BITBOARD >> (7-x))).count_ones() > 1{
-            score -= 10. * endgame_weight;
-        }
-
-        // black doubled pawns
-        if (black_pawn_bitboard & (VERTICLE_SLICE_BITBOARD >> (7-x))).count_ones() > 1{
-            score += 10. * endgame_weight;
-        }
-    }
-    
-    return score as i16
-}
-
 pub fn get_vert_side_bitboards(x: u8) -> u64{
     let mut bitboard: u64 = 0;
     if x != 7{
@@ -517,38 +479,154 @@ pub fn get_vert_side_bitboards(x: u8) -> u64{
     return bitboard;
 }
 
-// give big bonuses to position with confirmed promoting pawns
-pub fn promoting_pawn_score(board: &ChessBoard, board_color: bool, endgame_weight: f32) -> i16{
-    // has to be VERY deep endgame
-    if endgame_weight != 1.0 {
-        return 0;
+// squares in front of the pawn on its own file and on the files next to it
+fn get_pawn_front_spans(pawn_square: usize, board_color: bool) -> (u64, u64){
+    let pawn_x : u8 = pawn_square as u8 % 8;
+    let pawn_y : u8 = pawn_square as u8 / 8;
+
+    let infront_bb: u64;
+
+    if board_color{
+        infront_bb = !0 >> (8 * (8-pawn_y));
     }
+    else{
+        infront_bb = !0 << (8 * (pawn_y+1));
+    }
+
+    return ((VERTICLE_SLICE_BITBOARD >> (7-pawn_x)) & infront_bb, get_vert_side_bitboards(pawn_x) & infront_bb);
+}
 
+// everything here only depends on the pawns, get_pawn_structure caches it by the pawn hash
+pub fn analyse_pawn_structure(board: &ChessBoard) -> PawnStructure{
+    let mut pawn_structure: PawnStructure = EMPTY_PAWN_STRUCTURE;
+
+    pawn_structure.pawn_hash = board.pawn_hash;
+
+    for (color_index, board_color) in [true, false].into_iter().enumerate(){
+        let pawn_bitboard: u64;
+        let enemy_pawn_bitboard: u64;
+
+        if board_color{
+            pawn_bitboard = board.piece_bitboards[0];
+            enemy_pawn_bitboard = board.piece_bitboards[6];
+        }
+        else{
+            pawn_bitboard = board.piece_bitboards[6];
+            enemy_pawn_bitboard = board.piece_bitboards[0];
+        }
+
+        for x in 0..8{
+            if (pawn_bitboard & (VERTICLE_SLICE_BITBOARD >> (7-x))).count_ones() > 1{
+                pawn_structure.doubled_file_num[color_index] += 1;
+            }
+        }
+
+        let mut pawn_temp = pawn_bitboard;
+
+        while pawn_temp != 0{
+            let pawn_square : usize = pawn_temp.trailing_zeros() as usize;
+            let pawn_square_bitboard : u64 = 1 << pawn_square;
+
+            let pawn_x : u8 = pawn_square as u8 % 8;
+            let pawn_y : u8 = pawn_square as u8 / 8;
+
+            let (file_front_bb, side_front_bb) = get_pawn_front_spans(pawn_square, board_color);
+            let side_files_bb: u64 = get_vert_side_bitboards(pawn_x);
+
+            // nothing can stop it from promoting but pieces
+            if (file_front_bb | side_front_bb) & enemy_pawn_bitboard == 0 && file_front_bb & pawn_bitboard == 0{
+                pawn_structure.passed_pawns[color_index] |= pawn_square_bitboard;
+            }
+
+            let stop_square: usize = if board_color { pawn_square - 8 } else { pawn_square + 8 };
+            let is_stop_square_attacked: bool;
+
+            if board_color{
+                is_stop_square_attacked = WHITE_PAWN_ATTACK_MASK[stop_square] & enemy_pawn_bitboard != 0;
+            }
+            else{
+                is_stop_square_attacked = BLACK_PAWN_ATTACK_MASK[stop_square] & enemy_pawn_bitboard != 0;
+            }
+
+            // no pawns on the files next to it
+            if side_files_bb & pawn_bitboard == 0{
+                pawn_structure.isolated_pawns[color_index] |= pawn_square_bitboard;
+            }
+            // the pawns next to it are all further up so none of them can defend it
+            // and moving up just loses it to an enemy pawn
+            else if side_files_bb & !side_front_bb & pawn_bitboard == 0 && is_stop_square_attacked{
+                pawn_structure.backward_pawns[color_index] |= pawn_square_bitboard;
+            }
+
+            let defender_bitboard: u64 = if board_color { BLACK_PAWN_ATTACK_MASK[pawn_square] } else { WHITE_PAWN_ATTACK_MASK[pawn_square] };
+            let next_to_bitboard: u64 = side_files_bb & (HORIZONTAL_SLICE_BITBOARD >> (8 * (7 - pawn_y)));
+
+            if (defender_bitboard | next_to_bitboard) & pawn_bitboard != 0{
+                pawn_structure.connected_pawns[color_index] |= pawn_square_bitboard;
+            }
+
+            pawn_temp ^= pawn_square_bitboard;
+        }
+    }
+
+    return pawn_structure;
+}
+
+pub fn doubled_pawn_score(pawn_structure: &PawnStructure, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let color_index: usize = if board_color { 0 } else { 1 };
+
+    return make_eval_score(0, -(pawn_structure.doubled_file_num[color_index] as i16) * eval_params.doubled_pawn_penalty);
+}
+
+pub fn isolated_pawn_score(pawn_structure: &PawnStructure, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let color_index: usize = if board_color { 0 } else { 1 };
+
+    return -(pawn_structure.isolated_pawns[color_index].count_ones() as EvalScore) * make_eval_score(eval_params.isolated_pawn_penalty, eval_params.isolated_pawn_endgame_penalty);
+}
+
+pub fn backward_pawn_score(pawn_structure: &PawnStructure, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let color_index: usize = if board_color { 0 } else { 1 };
+
+    return -(pawn_structure.backward_pawns[color_index].count_ones() as EvalScore) * make_eval_score(eval_params.backward_pawn_penalty, eval_params.backward_pawn_endgame_penalty);
+}
+
+pub fn connected_pawn_score(pawn_structure: &PawnStructure, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let color_index: usize = if board_color { 0 } else { 1 };
+
+    return (pawn_structure.connected_pawns[color_index].count_ones() as EvalScore) * make_eval_score(eval_params.connected_pawn_bonus, eval_params.connected_pawn_endgame_bonus);
+}
+
+// give big bonuses to position with confirmed promoting pawns
+// board_color is the side the pawns belong to, the side to move decides who gets the extra tempo
+pub fn promoting_pawn_score(board: &ChessBoard, pawn_structure: &PawnStructure, board_color: bool, eval_params: &EvalParams) -> EvalScore{
     let white_pawn_bitboard : u64 = board.piece_bitboards[0];
     let black_pawn_bitboard: u64 = board.piece_bitboards[6];
 
     let white_king_bitboard: u64 = board.piece_bitboards[5];
     let black_king_bitboard: u64 = board.piece_bitboards[11];
 
-    let mut score : i16 = 0;
-
+    // has to be VERY deep endgame
     // there are only pawns and kings on the board
-    if (white_pawn_bitboard | black_pawn_bitboard | white_king_bitboard | black_king_bitboard) == board.all_piece_bitboard{
-        let mut white_pawn_temp = white_pawn_bitboard;
+    if (white_pawn_bitboard | black_pawn_bitboard | white_king_bitboard | black_king_bitboard) != board.all_piece_bitboard{
+        return ZERO_EVAL_SCORE;
+    }
 
-        let black_king_square = black_king_bitboard.trailing_zeros() as i8;
+    let white_color_offset: i8;
+    let black_color_offset: i8;
+    
+    if board.board_color{
+        black_color_offset = 0;
+        white_color_offset = 1;
+    }
+    else{
+        black_color_offset = 1;
+        white_color_offset = 0;
+    }
 
-        let white_color_offset: i8;
-        let black_color_offset: i8;
-        
-        if board_color{
-            black_color_offset = 0;
-            white_color_offset = 1;
-        }
-        else{
-            black_color_offset = 1;
-            white_color_offset = 0;
-        }
+    if board_color{
+        let mut white_pawn_temp = pawn_structure.passed_pawns[0];
+
+        let black_king_square = black_king_bitboard.trailing_zeros() as i8;
 
         while white_pawn_temp != 0{
             let pawn_square : usize = white_pawn_temp.trailing_zeros() as usize;
@@ -556,26 +634,16 @@ pub fn promoting_pawn_score(board: &ChessBoard, board_color: bool, endgame_weigh
             let pawn_x : i8 = pawn_square as i8 % 8;
             let pawn_y : i8 = pawn_square as i8 / 8;
     
-            let infront_bb: u64 = !0 >> (8 * (8-pawn_y));
-    
-            let side_bb: u64 = get_vert_side_bitboards(pawn_x as u8);
-    
-            let pawn_col_bb = (VERTICLE_SLICE_BITBOARD >> (7-pawn_x)) & infront_bb;
-    
-            let side_pawn_bb = side_bb & infront_bb;
-    
-            // the pawn is a pass pawn
-            if (side_pawn_bb | pawn_col_bb) & black_pawn_bitboard == 0 && pawn_col_bb & white_pawn_bitboard == 0{
-                if (black_king_square / 8 - black_color_offset > pawn_y) || (black_king_square % 8 - pawn_x).abs() - black_color_offset > pawn_y{
-                    score += 300;
-                    break;
-                }
+            // the king cant catch the pass pawn
+            if (black_king_square / 8 - black_color_offset > pawn_y) || (black_king_square % 8 - pawn_x).abs() - black_color_offset > pawn_y{
+                return make_eval_score(0, eval_params.unstoppable_pawn_bonus);
             }
     
             white_pawn_temp ^= 1 << pawn_square;
         }
-
-        let mut black_pawn_temp = black_pawn_bitboard;
+    }
+    else{
+        let mut black_pawn_temp = pawn_structure.passed_pawns[1];
 
         let white_king_square = white_king_bitboard.trailing_zeros() as i8;
 
@@ -585,118 +653,289 @@ pub fn promoting_pawn_score(board: &ChessBoard, board_color: bool, endgame_weigh
             let pawn_x : i8 = pawn_square as i8 % 8;
             let pawn_y : i8 = pawn_square as i8 / 8;
     
-            let infront_bb: u64 = !0 << (8 * (pawn_y+1));
-    
-            let side_bb: u64 = get_vert_side_bitboards(pawn_x as u8);
-    
-            // let is_accompanied = side_pawn_bitboard & black_pawn_bitboard != 0;
-            // let is_supported = WHITE_PAWN_ATTACK_MASK[pawn_square] & black_pawn_bitboard != 0;
-            let pawn_col_bb = (VERTICLE_SLICE_BITBOARD >> (7-pawn_x)) & infront_bb;
-    
-            let side_pawn_bb = side_bb & infront_bb;
-    
-            if (side_pawn_bb | pawn_col_bb) & white_pawn_bitboard == 0 && pawn_col_bb & black_pawn_bitboard == 0{
-                if (white_king_square / 8 + white_color_offset < pawn_y) || (white_king_square % 8 - pawn_x).abs() - white_color_offset > (7 - pawn_y){
-                    score -= 300;
-                    break;
-                }
+            if (white_king_square / 8 + white_color_offset < pawn_y) || (white_king_square % 8 - pawn_x).abs() - white_color_offset > (7 - pawn_y){
+                return make_eval_score(0, eval_params.unstoppable_pawn_bonus);
             }
+
             black_pawn_temp ^= 1 << pawn_square;
         }
     }
 
-    return score;
+    return ZERO_EVAL_SCORE;
+}
+
+// pass pawns
+pub fn pawn_surrounding_score(pawn_structure: &PawnStructure, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let mut score: i16 = 0;
+
+    let mut passed_pawn_temp: u64 = if board_color { pawn_structure.passed_pawns[0] } else { pawn_structure.passed_pawns[1] };
+
+    while passed_pawn_temp != 0{
+        let pawn_square : usize = passed_pawn_temp.trailing_zeros() as usize;
+
+        if board_color{
+            score += eval_params.passed_pawn_table[pawn_square];
+        }
+        else{
+            score += eval_params.passed_pawn_table[reverse_piece_square_index(pawn_square)];
+        }
+
+        passed_pawn_temp ^= 1 << pawn_square;
+    }
+
+    return make_eval_score(0, score);
 }
 
-// pass pawn, isolated pawn, 
-pub fn pawn_surrounding_score(board: &ChessBoard, endgame_weight: f32) -> i16{
+pub fn king_support_pawn_score(board: &ChessBoard, eval_params: &EvalParams) -> EvalScore{
+    let mut score: i16 = 0;
+
     let white_pawn_bitboard : u64 = board.piece_bitboards[0];
     let black_pawn_bitboard: u64 = board.piece_bitboards[6];
+    let white_king_square : usize = board.piece_bitboards[5].trailing_zeros() as usize;
+    let black_king_square : usize = board.piece_bitboards[11].trailing_zeros() as usize;
 
-    let mut white_pawn_temp = white_pawn_bitboard;
-    let mut black_pawn_temp = black_pawn_bitboard;
+    // println!("{}",(white_pawn_bitboard & KING_MOVE_MASK[white_king_square]).count_ones());
 
-    let mut score: i16 = 0;
+    score += clamp_int((white_pawn_bitboard & KING_MOVE_MASK[white_king_square]).count_ones() as i16, 0, 3) * eval_params.king_pawn_support_bonus;
+    score -= clamp_int((black_pawn_bitboard & KING_MOVE_MASK[black_king_square]).count_ones() as i16, 0, 3) * eval_params.king_pawn_support_bonus;
+    
+    return make_eval_score(0, score);
+}
 
-    while white_pawn_temp != 0{
-        let pawn_square : usize = white_pawn_temp.trailing_zeros() as usize;
-        
-        let pawn_x : u8 = pawn_square as u8 % 8;
-        let pawn_y : u8 = pawn_square as u8 / 8;
+pub fn get_attack_square_score(attack_bitboard : u64, eval_params: &EvalParams) -> EvalScore{
+    let mut score : i16 = 0;
+
+    score += attack_bitboard.count_ones() as i16 * eval_params.attack_square_bonus;
+
+    score += (attack_bitboard & IMPORTANT_ATTACK_SQUARES_MASK).count_ones() as i16 * eval_params.center_attack_square_bonus;
+
+    return make_eval_score(score, 0);
+}
+
+// the attacks of one side out of get_board_individual_attack_mask
+fn get_side_attack_bitboard(attack_arr: &[u64; 12], board_color: bool) -> u64{
+    let piece_offset: usize = if board_color { 0 } else { 6 };
+
+    let mut attack_bitboard: u64 = 0;
+
+    for piece_type in piece_offset..piece_offset+6{
+        attack_bitboard |= attack_arr[piece_type];
+    }
+
+    return attack_bitboard;
+}
+
+// squares an average piece can move to, anything above gets a bonus and anything below a penalty
+pub const MOBILITY_BASELINE: [i16; 4] = [6, 4, 6, 12];
+
+// counted per piece, squares with own pawns or king or attacked by enemy pawns dont count
+pub fn mobility_score(board: &ChessBoard, attack_arr: &[u64; 12], board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let piece_offset: usize;
+    let blocked_bitboard: u64;
+
+    if board_color{
+        piece_offset = 0;
+        blocked_bitboard = board.piece_bitboards[0] | board.piece_bitboards[5] | attack_arr[6];
+    }
+    else{
+        piece_offset = 6;
+        blocked_bitboard = board.piece_bitboards[6] | board.piece_bitboards[11] | attack_arr[0];
+    }
+
+    let mut score: EvalScore = ZERO_EVAL_SCORE;
+
+    // bishop, knight, rook, queen
+    for mobility_i in 0..4{
+        let piece_type: usize = piece_offset + mobility_i + 1;
+
+        let mut piece_temp: u64 = board.piece_bitboards[piece_type];
 
-        let infront_bb: u64 = !0 >> (8 * (8-pawn_y));
+        while piece_temp != 0{
+            let square: usize = piece_temp.trailing_zeros() as usize;
 
-        let side_bb: u64 = get_vert_side_bitboards(pawn_x);
+            let move_bitboard: u64 = match mobility_i{
+                0 => get_bishop_move_bitboard(square, board.all_piece_bitboard),
+                1 => KNIGHT_MOVE_MASK[square],
+                2 => get_rook_move_bitboard(square, board.all_piece_bitboard),
+                _ => get_queen_move_bitboard(square, board.all_piece_bitboard),
+            };
 
-        let pawn_col_bb = (VERTICLE_SLICE_BITBOARD >> (7-pawn_x)) & infront_bb;
+            let move_num: i16 = (move_bitboard & !blocked_bitboard).count_ones() as i16 - MOBILITY_BASELINE[mobility_i];
 
-        let side_pawn_bb = side_bb & infront_bb;
+            score += move_num as EvalScore * make_eval_score(eval_params.mobility_bonus[mobility_i], eval_params.mobility_endgame_bonus[mobility_i]);
 
-        if (side_pawn_bb | pawn_col_bb) & black_pawn_bitboard == 0 && pawn_col_bb & white_pawn_bitboard == 0{
-            score += PASS_PAWN_PIECE_SQUARE_TABLE[pawn_square as usize];
+            piece_temp ^= 1 << square;
         }
+    }
+
+    return score;
+}
+
+pub fn bishop_pair_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let bishop_bitboard: u64 = if board_color { board.piece_bitboards[1] } else { board.piece_bitboards[7] };
 
-        white_pawn_temp ^= 1 << pawn_square;
+    if bishop_bitboard.count_ones() < 2{
+        return ZERO_EVAL_SCORE;
     }
 
-    while black_pawn_temp != 0{
-        let pawn_square : usize = black_pawn_temp.trailing_zeros() as usize;        
-        
-        let pawn_x : u8 = pawn_square as u8 % 8;
-        let pawn_y : u8 = pawn_square as u8 / 8;
+    return make_eval_score(eval_params.bishop_pair_bonus, eval_params.bishop_pair_endgame_bonus);
+}
 
-        let infront_bb: u64 = !0 << (8 * (pawn_y+1));
+// open - no pawns on the file, semi open - only enemy pawns on the file
+pub fn rook_file_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let rook_bitboard: u64;
+    let pawn_bitboard: u64;
+    let enemy_pawn_bitboard: u64;
 
-        let side_bb: u64 = get_vert_side_bitboards(pawn_x);
+    if board_color{
+        rook_bitboard = board.piece_bitboards[3];
+        pawn_bitboard = board.piece_bitboards[0];
+        enemy_pawn_bitboard = board.piece_bitboards[6];
+    }
+    else{
+        rook_bitboard = board.piece_bitboards[9];
+        pawn_bitboard = board.piece_bitboards[6];
+        enemy_pawn_bitboard = board.piece_bitboards[0];
+    }
+
+    let mut score: EvalScore = ZERO_EVAL_SCORE;
 
-        // let is_accompanied = side_pawn_bitboard & black_pawn_bitboard != 0;
-        // let is_supported = WHITE_PAWN_ATTACK_MASK[pawn_square] & black_pawn_bitboard != 0;
-        let pawn_col_bb = (VERTICLE_SLICE_BITBOARD >> (7-pawn_x)) & infront_bb;
+    let mut rook_temp: u64 = rook_bitboard;
 
-        let side_pawn_bb = side_bb & infront_bb;
+    while rook_temp != 0{
+        let square: usize = rook_temp.trailing_zeros() as usize;
+        let file_bitboard: u64 = VERTICLE_SLICE_BITBOARD >> (7 - square % 8);
 
-        if (side_pawn_bb | pawn_col_bb) & white_pawn_bitboard == 0 && pawn_col_bb & black_pawn_bitboard == 0{
-            let reversed_pawn_square = reverse_piece_square_index(pawn_square) as usize;
-            score -= PASS_PAWN_PIECE_SQUARE_TABLE[reversed_pawn_square];
+        if file_bitboard & pawn_bitboard == 0{
+            if file_bitboard & enemy_pawn_bitboard == 0{
+                score += make_eval_score(eval_params.rook_open_file_bonus, eval_params.rook_open_file_endgame_bonus);
+            }
+            else{
+                score += make_eval_score(eval_params.rook_semi_open_file_bonus, eval_params.rook_semi_open_file_endgame_bonus);
+            }
         }
-        black_pawn_temp ^= 1 << pawn_square;
+
+        rook_temp ^= 1 << square;
     }
 
-    return (score as f32 * endgame_weight) as i16;
+    return score;
 }
 
-pub fn king_support_pawn_score(board: &ChessBoard, endgame_weight: f32) -> i16{
-    let mut score: i16 = 0;
+// only if there are pawns to eat there or the enemy king is stuck on the back rank
+pub fn rook_seventh_rank_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let rook_bitboard: u64;
+    let enemy_pawn_bitboard: u64;
+    let enemy_king_bitboard: u64;
+    let seventh_rank_bitboard: u64;
+    let eighth_rank_bitboard: u64;
 
-    let white_pawn_bitboard : u64 = board.piece_bitboards[0];
-    let black_pawn_bitboard: u64 = board.piece_bitboards[6];
-    let white_king_square : usize = board.piece_bitboards[5].trailing_zeros() as usize;
-    let black_king_square : usize = board.piece_bitboards[11].trailing_zeros() as usize;
+    if board_color{
+        rook_bitboard = board.piece_bitboards[3];
+        enemy_pawn_bitboard = board.piece_bitboards[6];
+        enemy_king_bitboard = board.piece_bitboards[11];
+        seventh_rank_bitboard = HORIZONTAL_SLICE_BITBOARD >> 48;
+        eighth_rank_bitboard = HORIZONTAL_SLICE_BITBOARD >> 56;
+    }
+    else{
+        rook_bitboard = board.piece_bitboards[9];
+        enemy_pawn_bitboard = board.piece_bitboards[0];
+        enemy_king_bitboard = board.piece_bitboards[5];
+        seventh_rank_bitboard = HORIZONTAL_SLICE_BITBOARD >> 8;
+        eighth_rank_bitboard = HORIZONTAL_SLICE_BITBOARD;
+    }
 
-    // println!("{}",(white_pawn_bitboard & KING_MOVE_MASK[white_king_square]).count_ones());
+    if enemy_pawn_bitboard & seventh_rank_bitboard == 0 && enemy_king_bitboard & eighth_rank_bitboard == 0{
+        return ZERO_EVAL_SCORE;
+    }
 
-    score += clamp_int((white_pawn_bitboard & KING_MOVE_MASK[white_king_square]).count_ones() as i16, 0, 3) * 20;
-    score -= clamp_int((black_pawn_bitboard & KING_MOVE_MASK[black_king_square]).count_ones() as i16, 0, 3) * 20;
-    
-    return int_float_mul(score, endgame_weight);
+    return (rook_bitboard & seventh_rank_bitboard).count_ones() as EvalScore * make_eval_score(eval_params.rook_seventh_rank_bonus, eval_params.rook_seventh_rank_endgame_bonus);
 }
 
-pub fn get_attack_square_score(mut white_attack_bitboard : u64, mut black_attack_bitboard: u64, inv_endgame_weight: f32) -> i16{
-    let mut score : i16 = 0;
+// 4th to 6th rank from each sides point of view
+const WHITE_OUTPOST_RANKS_BITBOARD: u64 = 0x000000FFFFFF0000;
+const BLACK_OUTPOST_RANKS_BITBOARD: u64 = 0x0000FFFFFF000000;
+
+// knight defended by a pawn that no enemy pawn can ever chase away
+pub fn knight_outpost_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let knight_bitboard: u64;
+    let pawn_bitboard: u64;
+    let enemy_pawn_bitboard: u64;
+
+    if board_color{
+        knight_bitboard = board.piece_bitboards[2] & WHITE_OUTPOST_RANKS_BITBOARD;
+        pawn_bitboard = board.piece_bitboards[0];
+        enemy_pawn_bitboard = board.piece_bitboards[6];
+    }
+    else{
+        knight_bitboard = board.piece_bitboards[8] & BLACK_OUTPOST_RANKS_BITBOARD;
+        pawn_bitboard = board.piece_bitboards[6];
+        enemy_pawn_bitboard = board.piece_bitboards[0];
+    }
+
+    let mut outpost_num: i32 = 0;
+
+    let mut knight_temp: u64 = knight_bitboard;
+
+    while knight_temp != 0{
+        let square: usize = knight_temp.trailing_zeros() as usize;
 
-    score += white_attack_bitboard.count_ones() as i16 * 2;
-    score -= black_attack_bitboard.count_ones() as i16 * 2;
+        let defender_bitboard: u64 = if board_color { BLACK_PAWN_ATTACK_MASK[square] } else { WHITE_PAWN_ATTACK_MASK[square] };
+        let (_, side_front_bb) = get_pawn_front_spans(square, board_color);
 
-    white_attack_bitboard &= IMPORTANT_ATTACK_SQUARES_MASK;
-    black_attack_bitboard &= IMPORTANT_ATTACK_SQUARES_MASK;
+        if defender_bitboard & pawn_bitboard != 0 && side_front_bb & enemy_pawn_bitboard == 0{
+            outpost_num += 1;
+        }
 
-    score += white_attack_bitboard.count_ones() as i16 * 10;
-    score -= black_attack_bitboard.count_ones() as i16 * 10;
+        knight_temp ^= 1 << square;
+    }
 
-    return int_float_mul(score, inv_endgame_weight);
+    return outpost_num * make_eval_score(eval_params.knight_outpost_bonus, eval_params.knight_outpost_endgame_bonus);
 }
 
-pub fn bishop_knight_endgame_bias(board: &ChessBoard, board_color: bool, endgame_weight: f32) -> i16{
+// enemy pieces attacked by pawns, and enemy pieces attacked but not defended at all
+// pawns and the king dont count as targets
+pub fn threat_score(board: &ChessBoard, attack_arr: &[u64; 12], board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let enemy_piece_bitboard: u64;
+    let pawn_attack_bitboard: u64;
+
+    if board_color{
+        enemy_piece_bitboard = board.black_piece_bitboard & !(board.piece_bitboards[6] | board.piece_bitboards[11]);
+        pawn_attack_bitboard = attack_arr[0];
+    }
+    else{
+        enemy_piece_bitboard = board.white_piece_bitboard & !(board.piece_bitboards[0] | board.piece_bitboards[5]);
+        pawn_attack_bitboard = attack_arr[6];
+    }
+
+    let attack_bitboard: u64 = get_side_attack_bitboard(attack_arr, board_color);
+    let enemy_attack_bitboard: u64 = get_side_attack_bitboard(attack_arr, !board_color);
+
+    let pawn_threat_num: i32 = (enemy_piece_bitboard & pawn_attack_bitboard).count_ones() as i32;
+    let hanging_piece_num: i32 = (enemy_piece_bitboard & attack_bitboard & !enemy_attack_bitboard).count_ones() as i32;
+
+    return pawn_threat_num * make_eval_score(eval_params.pawn_threat_bonus, eval_params.pawn_threat_endgame_bonus)
+        + hanging_piece_num * make_eval_score(eval_params.hanging_piece_bonus, eval_params.hanging_piece_endgame_bonus);
+}
+
+// c to f files on the 2nd to 4th rank from each sides point of view
+const WHITE_SPACE_BITBOARD: u64 = 0x003C3C3C00000000;
+const BLACK_SPACE_BITBOARD: u64 = 0x000000003C3C3C00;
+
+// central squares behind the pawns that the enemy pawns dont control
+pub fn space_score(board: &ChessBoard, attack_arr: &[u64; 12], board_color: bool, eval_params: &EvalParams) -> EvalScore{
+    let safe_bitboard: u64;
+
+    if board_color{
+        safe_bitboard = WHITE_SPACE_BITBOARD & !board.piece_bitboards[0] & !attack_arr[6];
+    }
+    else{
+        safe_bitboard = BLACK_SPACE_BITBOARD & !board.piece_bitboards[6] & !attack_arr[0];
+    }
+
+    return make_eval_score(safe_bitboard.count_ones() as i16 * eval_params.space_bonus, 0);
+}
+
+pub fn bishop_knight_endgame_bias(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
     
     let bishop_bitboard: u64;
     let opp_king_square: usize;
@@ -712,7 +951,7 @@ pub fn bishop_knight_endgame_bias(board: &ChessBoard, board_color: bool, endgame
 
     // no bishop
     if bishop_bitboard == 0{
-        return 0;
+        return ZERO_EVAL_SCORE;
     }
 
     let bishop_square = bishop_bitboard.trailing_zeros();
@@ -720,30 +959,20 @@ pub fn bishop_knight_endgame_bias(board: &ChessBoard, board_color: bool, endgame
     let bishop_color : bool = bishop_square % 8 == bishop_square / 8;
 
 
-    let score: f32;
     if bishop_color{
-        score = -BISHOP_KNIGHT_ENDGAME_BIAS_TABLE[opp_king_square] as f32 * endgame_weight;
+        return make_eval_score(0, -eval_params.bishop_knight_endgame_bias_table[opp_king_square]);
     }
     else{
-        score = BISHOP_KNIGHT_ENDGAME_BIAS_TABLE[opp_king_square] as f32 * endgame_weight;
-    }
-
-
-    if board_color{
-        return score as i16;
-    }
-    else{
-        // println!("{} {}", -score, opp_king_square);
-        return -score as i16;
+        return make_eval_score(0, eval_params.bishop_knight_endgame_bias_table[opp_king_square]);
     }
 }
 
 // -40 if king is no near pawns
 // 0 if 3 pawns surrounding
-const PAWN_SHIELD_PENALTY : [i16; 8] = [-40, -30, -10, 0, 5, 5, 5, 5];
+pub const PAWN_SHIELD_PENALTY : [i16; 8] = [-40, -30, -10, 0, 5, 5, 5, 5];
 
 // directly copied from stockfish... gosh I love open source
-const ATTACK_UNIT_TABLE: [i16; 100] = [
+pub const ATTACK_UNIT_TABLE: [i16; 100] = [
     0,  0,   1,   2,   3,   5,   7,   9,  12,  15,
   18,  22,  26,  30,  35,  39,  44,  50,  56,  62,
   68,  75,  82,  85,  89,  97, 105, 113, 122, 131,
@@ -759,11 +988,11 @@ const ATTACK_UNIT_TABLE: [i16; 100] = [
 // bishop and knight -> 2
 // rook -> 3
 // queen -> 5
-const PIECE_ATTACK_UNIT: [u8; 4] = [
+pub const PIECE_ATTACK_UNIT: [i16; 4] = [
     2, 2, 3, 5
 ];
 
-pub fn king_attack_unit_score(board: &ChessBoard, enemy_attack_bitboard: u64 , board_color: bool, inv_endgame_weight: f32) -> i16{
+pub fn king_attack_unit_score(board: &ChessBoard, enemy_attack_bitboard: u64 , board_color: bool, eval_params: &EvalParams) -> EvalScore{
     let king_square: u8;
     let king_infront_rows_bitboard: u64;
 
@@ -799,7 +1028,7 @@ pub fn king_attack_unit_score(board: &ChessBoard, enemy_attack_bitboard: u64 , b
     let king_diag_vul = KING_DIAG_VULNERABLE_MASK[king_square as usize];
     
     // attack unit score
-    let mut attack_unit_num: u8 = 0;
+    let mut attack_unit_num: i16 = 0;
 
     let king_zone : u64 = KING_MOVE_MASK[king_square as usize] | (king_infront_rows_bitboard & VERTICLE_SLICE_BITBOARD >> (7-king_x)) ^ (1<<king_square);
 
@@ -812,7 +1041,7 @@ pub fn king_attack_unit_score(board: &ChessBoard, enemy_attack_bitboard: u64 , b
     while b_temp != 0{
         let square = b_temp.trailing_zeros();
 
-        attack_unit_num += (BISHOP_MOVE_MASK[square as usize] & king_zone_attack).count_ones() as u8 * PIECE_ATTACK_UNIT[0];
+        attack_unit_num += (BISHOP_MOVE_MASK[square as usize] & king_zone_attack).count_ones() as i16 * eval_params.piece_attack_units[0];
 
         b_temp ^= 1 << square;
     }
@@ -823,7 +1052,7 @@ pub fn king_attack_unit_score(board: &ChessBoard, enemy_attack_bitboard: u64 , b
     while r_temp != 0{
         let square = r_temp.trailing_zeros();
 
-        attack_unit_num += (ROOK_MOVE_MASK[square as usize] & king_zone_attack).count_ones() as u8 * PIECE_ATTACK_UNIT[2];
+        attack_unit_num += (ROOK_MOVE_MASK[square as usize] & king_zone_attack).count_ones() as i16 * eval_params.piece_attack_units[2];
 
         r_temp ^= 1 << square;
     }
@@ -832,7 +1061,7 @@ pub fn king_attack_unit_score(board: &ChessBoard, enemy_attack_bitboard: u64 , b
     while knight_bb != 0{
         let knight_square = knight_bb.trailing_zeros();
 
-        attack_unit_num += (KNIGHT_MOVE_MASK[knight_square as usize] & king_zone_attack).count_ones() as u8 * PIECE_ATTACK_UNIT[1];
+        attack_unit_num += (KNIGHT_MOVE_MASK[knight_square as usize] & king_zone_attack).count_ones() as i16 * eval_params.piece_attack_units[1];
 
         knight_bb ^= 1 << knight_square;
     }
@@ -841,13 +1070,15 @@ pub fn king_attack_unit_score(board: &ChessBoard, enemy_attack_bitboard: u64 , b
     if queen_bb != 0{
         let queen_square = queen_bb.trailing_zeros();
 
-        attack_unit_num += (QUEEN_MOVE_MASK[queen_square as usize] & king_zone_attack).count_ones() as u8 * PIECE_ATTACK_UNIT[3];
+        attack_unit_num += (QUEEN_MOVE_MASK[queen_square as usize] & king_zone_attack).count_ones() as i16 * eval_params.piece_attack_units[3];
     }
 
-    return -int_float_mul(ATTACK_UNIT_TABLE[attack_unit_num as usize], inv_endgame_weight);
+    let attack_unit_index = clamp_int(attack_unit_num, 0, eval_params.attack_unit_table.len() as i16 - 1) as usize;
+
+    return make_eval_score(-eval_params.attack_unit_table[attack_unit_index], 0);
 }
 
-pub fn king_safety_score(board: &ChessBoard, board_color: bool, inv_endgame_weight: f32) -> i16{
+pub fn king_safety_score(board: &ChessBoard, board_color: bool, eval_params: &EvalParams) -> EvalScore{
     let king_square: u8;
     let king_infront_rows_bitboard: u64;
     let friendly_piece_bitboard: u64;
@@ -873,126 +1104,196 @@ pub fn king_safety_score(board: &ChessBoard, board_color: bool, inv_endgame_weig
     // to the right
     if king_x != 7{
         if VERTICLE_SLICE_BITBOARD >> (7-king_x - 1) & friendly_piece_bitboard == 0{
-            score -= 20;
+            score -= eval_params.king_open_file_penalty;
         }
     }
 
     // to the left
     if king_x != 0{
         if VERTICLE_SLICE_BITBOARD >> (7-king_x + 1) & friendly_piece_bitboard == 0{
-            score -= 20;
+            score -= eval_params.king_open_file_penalty;
         }
     }
 
     // no close pawns penalty
     let close_pawns_num: usize = (KING_MOVE_MASK[king_square as usize] & friendly_pawn_bitboard).count_ones() as usize;
 
-    score += PAWN_SHIELD_PENALTY[close_pawns_num];
+    score += eval_params.pawn_shield_penalty[close_pawns_num];
 
-    return int_float_mul(score, inv_endgame_weight);
+    return make_eval_score(score, 0);
 } 
 
+// only the parts make_move keeps up to date
 pub fn get_cheap_board_score(board: &ChessBoard) -> i16{
-    let mut score: i16 = 0;
+    let mut score: EvalScore = ZERO_EVAL_SCORE;
 
-    let endgame_weight : f32 = get_endgame_weight(board); 
+    score += board.material_scores[0] - board.material_scores[1];
 
-    score += get_board_piece_value_score(board);
+    score += board.piece_square_scores[0] - board.piece_square_scores[1];
 
-    score += get_board_piece_square_score(board);
+    let tapered_score = taper_eval_score(score, get_endgame_weight(board), ENDGAME_WEIGHT_MAX);
     
     if board.board_color{
-        return score;
+        return tapered_score;
     }
     else{
-        return -score;
+        return -tapered_score;
     }
 }
 
-pub fn get_board_score(board: &ChessBoard) -> i16{
-    let mut score: i16 = 0;
-
-    let endgame_weight : f32 = get_endgame_weight(board); 
-    let inv_endgame_weight: f32 = 1.0 - endgame_weight;
-
-    
-    score += get_board_piece_square_score(board);
-
-    score += get_board_piece_value_score(board);
+pub fn get_board_score(board: &ChessBoard, eval_params: &EvalParams) -> i16{
+    return evaluate_board(board, eval_params, None);
+}
 
-    // prioritises pawn near end nearer to endgame
-    score += get_pawn_piece_square_score(board, endgame_weight);
+// same evaluation as get_board_score, with every term kept apart
+pub fn get_eval_trace(board: &ChessBoard, eval_params: &EvalParams) -> EvalTrace{
+    let mut eval_trace = EvalTrace::new();
 
-    // prioritises king near center
-    score += king_endgame_square_weight(board, endgame_weight);
-    
-    // heavy evals / inv endgame affected scoring
+    evaluate_board(board, eval_params, Some(&mut eval_trace));
 
-    if inv_endgame_weight > 0.1{
-        // let mut ind_piece_attack_squares: [u64; 12] = [0;12];
+    return eval_trace;
+}
 
-        // get_board_individual_attack_mask(board, &mut ind_piece_attack_squares);
+// both scores are from the sides own point of view
+fn add_eval_term(score: &mut EvalScore, eval_trace: &mut Option<&mut EvalTrace>, name: &'static str, white_score: EvalScore, black_score: EvalScore){
+    *score += white_score - black_score;
 
-        // let white_attack_bitboard : u64 = or_together(&ind_piece_attack_squares[0..6]);
-        // let black_attack_bitboard : u64 = or_together(&ind_piece_attack_squares[6..12]);
+    if let Some(eval_trace) = eval_trace{
+        eval_trace.add_term(name, white_score, black_score);
+    }
+}
 
-        let white_attack_bitboard: u64;
-        let black_attack_bitboard: u64;
+// every term adds a middlegame and endgame score, they are blended once at the end by the endgame weight
+fn evaluate_board(board: &ChessBoard, eval_params: &EvalParams, mut eval_trace: Option<&mut EvalTrace>) -> i16{
+    debug_assert!(check_eval_accumulators(board, eval_params), "eval accumulators out of date: {}", board_to_fen(board));
+
+    // endings with their own evaluation skip everything else
+    if let Some((endgame_name, endgame_score)) = probe_endgame_evaluators(board, eval_params){
+        if let Some(eval_trace) = eval_trace{
+            eval_trace.endgame_name = Some(endgame_name);
+            eval_trace.endgame_weight = get_endgame_weight(board);
+            eval_trace.score = make_eval_score(endgame_score, endgame_score);
+            eval_trace.tapered_score = endgame_score;
+            eval_trace.board_color = board.board_color;
+        }
 
         if board.board_color{
-            white_attack_bitboard = get_board_attack_mask(board, true);
-            black_attack_bitboard = board.attack_mask;
+            return endgame_score;
         }
         else{
-            white_attack_bitboard = board.attack_mask;
-            black_attack_bitboard = get_board_attack_mask(board, false);
+            return -endgame_score;
         }
+    }
 
-        // white_attack_bitboard = get_board_attack_mask(board, true);
-        // black_attack_bitboard = get_board_attack_mask(board, true);
-        
-        // print_board(board);
+    let mut score: EvalScore = ZERO_EVAL_SCORE;
 
-        // print_bitboard(white_attack_bitboard);
-        // print_bitboard(black_attack_bitboard);
+    let endgame_weight : i16 = get_endgame_weight(board); 
 
-        // incentivises control over center and piece mobility
-        score += get_attack_square_score(white_attack_bitboard, black_attack_bitboard, inv_endgame_weight);
+    // material and piece squares are kept up to date by make_move
+    add_eval_term(&mut score, &mut eval_trace, "material", board.material_scores[0], board.material_scores[1]);
+
+    if eval_trace.is_some(){
+        // the trace splits the piece square total back up
+        add_eval_term(&mut score, &mut eval_trace, "piece squares", get_board_piece_square_score(board, true, eval_params), get_board_piece_square_score(board, false, eval_params));
 
-        score += king_safety_score(board, true, inv_endgame_weight);
-        score -= king_safety_score(board, false, inv_endgame_weight);
+        // prioritises pawn near end nearer to endgame
+        add_eval_term(&mut score, &mut eval_trace, "pawn squares", get_pawn_piece_square_score(board, true, eval_params), get_pawn_piece_square_score(board, false, eval_params));
 
-        // score += king_attack_unit_score(board, black_attack_bitboard, true, inv_endgame_weight);
-        // score -= king_attack_unit_score(board, white_attack_bitboard, false, inv_endgame_weight);
+        // prioritises king near center
+        add_eval_term(&mut score, &mut eval_trace, "king squares", king_endgame_square_weight(board, true, eval_params), king_endgame_square_weight(board, false, eval_params));
+    }
+    else{
+        score += board.piece_square_scores[0] - board.piece_square_scores[1];
     }
     
-    
+    // middlegame only terms
+    // white then black, with only pawns and kings left they wouldnt count so they arent computed
+
+    let mut attack_square_scores: [EvalScore; 2] = [ZERO_EVAL_SCORE; 2];
+    let mut king_safety_scores: [EvalScore; 2] = [ZERO_EVAL_SCORE; 2];
+    let mut space_scores: [EvalScore; 2] = [ZERO_EVAL_SCORE; 2];
 
-    // endgame affected scoring
+    // both phases, but they only look at pieces so they are zero when the middlegame terms are skipped
+    let mut mobility_scores: [EvalScore; 2] = [ZERO_EVAL_SCORE; 2];
+    let mut threat_scores: [EvalScore; 2] = [ZERO_EVAL_SCORE; 2];
 
-    if endgame_weight > 0.1{
-        // wants king to be closer to other king
-        score += king_distance_weight(board, endgame_weight);
+    if endgame_weight < ENDGAME_WEIGHT_MAX{
+        let mut attack_arr: [u64; 12] = [0; 12];
 
-        // doubled pawn penalty
-        score += doubled_pawn_score(board, endgame_weight);
-        score += pawn_surrounding_score(board, endgame_weight);
+        get_board_individual_attack_mask(board, &mut attack_arr);
 
-        // bishop knight endgame - king square favours corners
-        score += bishop_knight_endgame_bias(board, true, endgame_weight);
-        score += bishop_knight_endgame_bias(board, false, endgame_weight);
+        let white_attack_bitboard: u64 = get_side_attack_bitboard(&attack_arr, true);
+        let black_attack_bitboard: u64 = get_side_attack_bitboard(&attack_arr, false);
 
-        // promoting pawn bonus
-        score += promoting_pawn_score(board, board.board_color, endgame_weight);
+        // incentivises control over center and piece mobility
+        attack_square_scores = [get_attack_square_score(white_attack_bitboard, eval_params), get_attack_square_score(black_attack_bitboard, eval_params)];
+
+        king_safety_scores = [king_safety_score(board, true, eval_params), king_safety_score(board, false, eval_params)];
+
+        space_scores = [space_score(board, &attack_arr, true, eval_params), space_score(board, &attack_arr, false, eval_params)];
+
+        mobility_scores = [mobility_score(board, &attack_arr, true, eval_params), mobility_score(board, &attack_arr, false, eval_params)];
+        threat_scores = [threat_score(board, &attack_arr, true, eval_params), threat_score(board, &attack_arr, false, eval_params)];
+
+        // king_attack_unit_score(board, black_attack_bitboard, true, eval_params);
+        // king_attack_unit_score(board, white_attack_bitboard, false, eval_params);
     }
 
-    
+    add_eval_term(&mut score, &mut eval_trace, "attack squares", attack_square_scores[0], attack_square_scores[1]);
+    add_eval_term(&mut score, &mut eval_trace, "king safety", king_safety_scores[0], king_safety_scores[1]);
+    add_eval_term(&mut score, &mut eval_trace, "space", space_scores[0], space_scores[1]);
+
+    // piece terms
+    add_eval_term(&mut score, &mut eval_trace, "mobility", mobility_scores[0], mobility_scores[1]);
+    add_eval_term(&mut score, &mut eval_trace, "threats", threat_scores[0], threat_scores[1]);
+    add_eval_term(&mut score, &mut eval_trace, "bishop pair", bishop_pair_score(board, true, eval_params), bishop_pair_score(board, false, eval_params));
+    add_eval_term(&mut score, &mut eval_trace, "rook files", rook_file_score(board, true, eval_params), rook_file_score(board, false, eval_params));
+    add_eval_term(&mut score, &mut eval_trace, "rook 7th rank", rook_seventh_rank_score(board, true, eval_params), rook_seventh_rank_score(board, false, eval_params));
+    add_eval_term(&mut score, &mut eval_trace, "knight outposts", knight_outpost_score(board, true, eval_params), knight_outpost_score(board, false, eval_params));
+
+    // endgame only terms
+
+    // wants king to be closer to other king
+    add_eval_term(&mut score, &mut eval_trace, "king distance", king_distance_weight(board, true, eval_params), king_distance_weight(board, false, eval_params));
+
+    // pawn structure, mostly straight out of the pawn hash table
+    let pawn_structure = get_pawn_structure(board);
+
+    // doubled pawn penalty
+    add_eval_term(&mut score, &mut eval_trace, "doubled pawns", doubled_pawn_score(&pawn_structure, true, eval_params), doubled_pawn_score(&pawn_structure, false, eval_params));
+    add_eval_term(&mut score, &mut eval_trace, "pawn surroundings", pawn_surrounding_score(&pawn_structure, true, eval_params), pawn_surrounding_score(&pawn_structure, false, eval_params));
+    add_eval_term(&mut score, &mut eval_trace, "isolated pawns", isolated_pawn_score(&pawn_structure, true, eval_params), isolated_pawn_score(&pawn_structure, false, eval_params));
+    add_eval_term(&mut score, &mut eval_trace, "backward pawns", backward_pawn_score(&pawn_structure, true, eval_params), backward_pawn_score(&pawn_structure, false, eval_params));
+    add_eval_term(&mut score, &mut eval_trace, "connected pawns", connected_pawn_score(&pawn_structure, true, eval_params), connected_pawn_score(&pawn_structure, false, eval_params));
+
+    // bishop knight endgame - king square favours corners
+    add_eval_term(&mut score, &mut eval_trace, "bishop knight bias", bishop_knight_endgame_bias(board, true, eval_params), bishop_knight_endgame_bias(board, false, eval_params));
+
+    // promoting pawn bonus
+    add_eval_term(&mut score, &mut eval_trace, "promoting pawns", promoting_pawn_score(board, &pawn_structure, true, eval_params), promoting_pawn_score(board, &pawn_structure, false, eval_params));
+
+    // drawish material keeps less of the endgame score
+    let scale_factor = get_scale_factor(board, get_eg_score(score) >= 0, eval_params);
+
+    if scale_factor != SCALE_FACTOR_NORMAL{
+        score = scale_eg_score(score, scale_factor, SCALE_FACTOR_NORMAL);
+    }
+
+    let tapered_score = taper_eval_score(score, endgame_weight, ENDGAME_WEIGHT_MAX);
+
+    if let Some(eval_trace) = eval_trace{
+        eval_trace.scale_factor = scale_factor;
+        eval_trace.endgame_weight = endgame_weight;
+        eval_trace.score = score;
+        eval_trace.tapered_score = tapered_score;
+        eval_trace.board_color = board.board_color;
+    }
 
     // relative evaluation due to negamax
     if board.board_color{
-        return score;
+        return tapered_score;
     }
     else{
-        return -score;
+        return -tapered_score;
     }   
 }
@@ -47,6 +47,19 @@ pub fn get_move_string(mv: u16) -> String{
     return format!("{}{}", num_to_coord(from_pos), num_to_coord(to_pos));
 }
 
+const PROMOTION_CHARS: [char; 4] = ['b', 'n', 'r', 'q'];
+
+// move string with the promotion piece at the end like uci wants it
+pub fn get_uci_move_string(mv: u16) -> String{
+    let special: u16 = mv >> 12;
+
+    if special >= 5 && special <= 8{
+        return format!("{}{}", get_move_string(mv), PROMOTION_CHARS[(special - 5) as usize]);
+    }
+
+    return get_move_string(mv);
+}
+
 pub fn print_move_command_debug(mv: u16){
     // special move
     if mv >> 12 != 0{
@@ -120,19 +133,6 @@ pub fn coord_to_number(coordinate: &str) -> u8 {
     return row_index * 8 + (7-column_index);
 }
 
-pub fn lerp(val1: f32, val2:f32, n:f32) -> f32{
-    return (1.0-n) * val1 + n * val2;
-}
-
-// is this ugly? yes but who cares
-pub fn lerp_int(val1: i16, val2:i16, n:f32) -> i16{
-    return lerp(val1 as f32, val2 as f32, n) as i16;
-}
-
-pub fn int_float_mul(val1: i16, val2: f32) -> i16{
-    return (val1 as f32 * val2) as i16;
-}
-
 pub fn get_manhattan_distance(square1: i16, square2: i16) -> u16{
     let x1: i16 = square1 % 8;
     let y1: i16 = square1 / 8;
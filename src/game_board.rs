@@ -1,7 +1,11 @@
 use crate::board::*;
 use crate::transposition_table::TranspositionTable;
 use crate::history_heuristic::HistoryHueristicTable;
+use crate::search_stats::SearchStats;
+use crate::eval_params::EvalParams;
+use crate::eval_accumulator::*;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 // a game board is used to hold the transposition table and move tree
 // Game Boards should not be used recursively
@@ -10,9 +14,13 @@ use std::collections::HashMap;
 pub struct GameChessBoard{
     pub board: ChessBoard,
     pub game_tree: HashMap<u64, u8>,
-    pub transposition_table: TranspositionTable,
+    // shared by every clone of the board, a search locks it while it runs
+    pub transposition_table: Arc<Mutex<TranspositionTable>>,
     pub hh_table:  HistoryHueristicTable,
     pub move_limit: u16,
+    // stats of the last search done on this board
+    pub search_stats: SearchStats,
+    pub eval_params: EvalParams,
 }
 
 impl Clone for GameChessBoard {
@@ -20,9 +28,12 @@ impl Clone for GameChessBoard {
         let mut game_board = GameChessBoard{
             board: self.board.clone(),
             game_tree: self.game_tree.clone(),
+            // searches on a clone fill the same table instead of copying it
             transposition_table: self.transposition_table.clone(),
             hh_table: self.hh_table.clone(),
             move_limit: 0,
+            search_stats: self.search_stats.clone(),
+            eval_params: self.eval_params.clone(),
         };
         
         return game_board;
@@ -31,21 +42,25 @@ impl Clone for GameChessBoard {
 
 pub fn create_empty_GameChessBoard() -> GameChessBoard{
     return GameChessBoard{
-        board: create_empty_board(),
+        board: create_empty_board(&get_default_eval_tables()),
         game_tree: HashMap::new(),
-        transposition_table: TranspositionTable::new(),
+        transposition_table: Arc::new(Mutex::new(TranspositionTable::new())),
         hh_table: HistoryHueristicTable::new(),
         move_limit: 0,
+        search_stats: SearchStats::new(),
+        eval_params: EvalParams::new(),
     }
 }
 
 pub fn fen_to_GameChessBoard(s: &str) -> GameChessBoard{
     let mut game_board = GameChessBoard{
-        board: fen_to_board(s),
+        board: fen_to_board(s, &get_default_eval_tables()),
         game_tree: HashMap::new(),
-        transposition_table: TranspositionTable::new(),
+        transposition_table: Arc::new(Mutex::new(TranspositionTable::new())),
         hh_table: HistoryHueristicTable::new(),
         move_limit: 0,
+        search_stats: SearchStats::new(),
+        eval_params: EvalParams::new(),
     };
 
     add_to_game_tree(&mut game_board.game_tree, game_board.board.zobrist_hash);
@@ -54,6 +69,25 @@ pub fn fen_to_GameChessBoard(s: &str) -> GameChessBoard{
 }
 
 
+// sets up a new position but keeps the (possibly resized) transposition table, history and eval tables
+pub fn set_GameChessBoard_fen(game_board: &mut GameChessBoard, s: &str){
+    let eval_tables = game_board.board.eval_tables.clone();
+
+    game_board.board = fen_to_board(s, &eval_tables);
+    game_board.game_tree.clear();
+    game_board.move_limit = 0;
+
+    add_to_game_tree(&mut game_board.game_tree, game_board.board.zobrist_hash);
+}
+
+// has to be called after game_board.eval_params changed
+// the piece square scores make_move uses come from the parameters and the old search results dont hold anymore
+pub fn update_GameChessBoard_eval_params(game_board: &mut GameChessBoard){
+    game_board.board.eval_tables = Arc::new(EvalAccumulatorTables::new(&game_board.eval_params));
+    update_eval_accumulators(&mut game_board.board);
+
+    game_board.transposition_table.lock().unwrap().clear();
+}
 
 // this is just normal move making but with game tree stuff
 pub fn game_make_move(chess_board: &mut GameChessBoard, mv: u16){
@@ -61,11 +95,6 @@ pub fn game_make_move(chess_board: &mut GameChessBoard, mv: u16){
     
     add_to_game_tree(&mut chess_board.game_tree, chess_board.board.zobrist_hash);
 
-    // should drain the TT
-    if chess_board.transposition_table.exceed_size(){
-        chess_board.transposition_table.drain();
-    }
-
 
     chess_board.move_limit += 1;
 }
@@ -1,5 +1,6 @@
 use crate::move_compute::*;
 use crate::functions::*;
+use crate::board::ChessBoard;
 
 #[derive(Clone)]
 pub struct HistoryHueristicTable{
@@ -7,7 +8,17 @@ pub struct HistoryHueristicTable{
     pub hh_table: [[[i16;64];64];2],
 }
 
-const MAX_HISTORY: i16 = 500;
+pub const MAX_HISTORY: i16 = 500;
+
+// moves the entry towards the bonus, the closer it already is the smaller the step
+// (history gravity) so entries stay within MAX_HISTORY
+fn apply_history_bonus(entry: &mut i16, bonus: i16){
+    let clamped_bonus = clamp_int(bonus, -MAX_HISTORY, MAX_HISTORY) as i32;
+
+    let difference = clamped_bonus - (*entry as i32 * clamped_bonus.abs()) / MAX_HISTORY as i32;
+
+    *entry += difference as i16;
+}
 
 impl HistoryHueristicTable{
     pub fn new() -> HistoryHueristicTable{
@@ -33,13 +44,7 @@ impl HistoryHueristicTable{
             color_index = 1;
         }
 
-        let clamped_bonus = clamp_int(bonus, -MAX_HISTORY, MAX_HISTORY);
-
-        let prev_value = self.hh_table[color_index][from][to];
-
-        let difference = clamped_bonus - (prev_value * clamped_bonus.abs()) / MAX_HISTORY;
-
-        self.hh_table[color_index][from][to] += difference;
+        apply_history_bonus(&mut self.hh_table[color_index][from][to], bonus);
     }
     
     pub fn age(&mut self){
@@ -109,4 +114,113 @@ impl HistoryHueristicTable{
         }
         println!("");
     }
-}
\ No newline at end of file
+}
+
+// piece (0-11) and target square of a move packed into one index
+// has to be computed before the move is made
+pub const NO_PIECE_TO: u16 = 12 * 64;
+
+pub fn get_piece_to(board: &ChessBoard, mv: u16) -> u16{
+    let from: usize = (mv & MOVE_DECODER_MASK) as usize;
+    let to: u16 = (mv >> 6) & MOVE_DECODER_MASK;
+
+    return (board.piece_array[from] as u16 - 1) * 64 + to;
+}
+
+// how good a move was depending on the move(s) before it
+// [previous piece to][piece to]
+// the 1-ply table follows the opponents last move, the 2-ply table our own last move
+#[derive(Clone)]
+pub struct ContinuationHistoryTable{
+    pub table: Box<[[i16; 768]]>,
+}
+
+impl ContinuationHistoryTable{
+    pub fn new() -> ContinuationHistoryTable{
+        ContinuationHistoryTable{
+            table: vec![[0; 768]; 768].into_boxed_slice()
+        }
+    }
+
+    pub fn update(&mut self, prev_piece_to: u16, piece_to: u16, bonus: i16){
+        if prev_piece_to == NO_PIECE_TO{
+            return;
+        }
+
+        apply_history_bonus(&mut self.table[prev_piece_to as usize][piece_to as usize], bonus);
+    }
+
+    pub fn get(&self, prev_piece_to: u16, piece_to: u16) -> i16{
+        if prev_piece_to == NO_PIECE_TO{
+            return 0;
+        }
+
+        return self.table[prev_piece_to as usize][piece_to as usize];
+    }
+}
+
+// how good a capture was
+// [moving piece][to][captured piece type]
+#[derive(Clone)]
+pub struct CaptureHistoryTable{
+    pub table: [[[i16; 6]; 64]; 12],
+}
+
+impl CaptureHistoryTable{
+    pub fn new() -> CaptureHistoryTable{
+        CaptureHistoryTable{
+            table: [[[0; 6]; 64]; 12]
+        }
+    }
+
+    fn get_indices(board: &ChessBoard, mv: u16) -> (usize, usize, usize){
+        let from: usize = (mv & MOVE_DECODER_MASK) as usize;
+        let to: usize = ((mv >> 6) & MOVE_DECODER_MASK) as usize;
+
+        let piece: usize = (board.piece_array[from] - 1) as usize;
+
+        // en passant lands on an empty square
+        let captured_piece_type: usize = if board.piece_array[to] == 0 { 0 } else { ((board.piece_array[to] - 1) % 6) as usize };
+
+        return (piece, to, captured_piece_type);
+    }
+
+    pub fn update(&mut self, board: &ChessBoard, mv: u16, bonus: i16){
+        let (piece, to, captured_piece_type) = CaptureHistoryTable::get_indices(board, mv);
+
+        apply_history_bonus(&mut self.table[piece][to][captured_piece_type], bonus);
+    }
+
+    pub fn get(&self, board: &ChessBoard, mv: u16) -> i16{
+        let (piece, to, captured_piece_type) = CaptureHistoryTable::get_indices(board, mv);
+
+        return self.table[piece][to][captured_piece_type];
+    }
+}
+
+// history tables that only live for a single search
+pub struct SearchHistoryTables{
+    pub continuation_1ply: ContinuationHistoryTable,
+    pub continuation_2ply: ContinuationHistoryTable,
+    pub capture_history: CaptureHistoryTable,
+}
+
+impl SearchHistoryTables{
+    pub fn new() -> SearchHistoryTables{
+        SearchHistoryTables{
+            continuation_1ply: ContinuationHistoryTable::new(),
+            continuation_2ply: ContinuationHistoryTable::new(),
+            capture_history: CaptureHistoryTable::new(),
+        }
+    }
+
+    // prev_piece_tos holds the piece to of the last move and the one before that
+    pub fn get_continuation(&self, prev_piece_tos: [u16; 2], piece_to: u16) -> i16{
+        return self.continuation_1ply.get(prev_piece_tos[0], piece_to) + self.continuation_2ply.get(prev_piece_tos[1], piece_to);
+    }
+
+    pub fn update_continuation(&mut self, prev_piece_tos: [u16; 2], piece_to: u16, bonus: i16){
+        self.continuation_1ply.update(prev_piece_tos[0], piece_to, bonus);
+        self.continuation_2ply.update(prev_piece_tos[1], piece_to, bonus);
+    }
+}
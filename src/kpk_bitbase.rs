@@ -0,0 +1,231 @@
+use std::sync::OnceLock;
+
+use crate::board::*;
+use crate::move_compute::*;
+
+// won / drawn for every king and pawn vs king position, worked out backwards from the positions
+// that are decided straight away (promotions, stalemates, the pawn getting taken)
+// generated the first time it gets probed
+
+// the strong side is always white here, so the pawn moves towards square 0
+// pawns on the e to h files are mirrored onto the a to d files
+// index: pawn (a to d file, 7th to 2nd rank), weak king, strong king, side to move
+const KPK_PAWN_SQUARE_NUM: usize = 24;
+pub const KPK_POSITION_NUM: usize = KPK_PAWN_SQUARE_NUM * 64 * 64 * 2;
+
+// results during generation, invalid positions dont change anything when or-ed together
+const KPK_INVALID: u8 = 0;
+const KPK_UNKNOWN: u8 = 1;
+const KPK_DRAW: u8 = 2;
+const KPK_WIN: u8 = 4;
+
+// one bit per position, set when the strong side wins
+static KPK_BITBASE: OnceLock<Vec<u64>> = OnceLock::new();
+
+fn get_kpk_index(strong_king_square: usize, pawn_square: usize, weak_king_square: usize, strong_to_move: bool) -> usize{
+    let pawn_index = (pawn_square / 8 - 1) * 4 + pawn_square % 8;
+
+    return ((pawn_index * 64 + weak_king_square) * 64 + strong_king_square) * 2 + strong_to_move as usize;
+}
+
+fn get_kpk_squares(index: usize) -> (usize, usize, usize, bool){
+    let strong_to_move = index % 2 == 1;
+    let strong_king_square = (index / 2) % 64;
+    let weak_king_square = (index / 128) % 64;
+    let pawn_index = index / 8192;
+
+    let pawn_square = (pawn_index / 4 + 1) * 8 + pawn_index % 4;
+
+    return (strong_king_square, pawn_square, weak_king_square, strong_to_move);
+}
+
+// the result of the position without looking at any moves
+fn get_initial_kpk_result(strong_king_square: usize, pawn_square: usize, weak_king_square: usize, strong_to_move: bool) -> u8{
+    let strong_king_bitboard: u64 = 1 << strong_king_square;
+    let weak_king_bitboard: u64 = 1 << weak_king_square;
+    let pawn_bitboard: u64 = 1 << pawn_square;
+
+    let pawn_attack_bitboard = WHITE_PAWN_ATTACK_MASK[pawn_square];
+
+    // kings next to each other, pieces on top of each other, or the weak king in check with the strong side to move
+    if strong_king_square == weak_king_square
+    || KING_MOVE_MASK[strong_king_square] & weak_king_bitboard != 0
+    || (strong_king_bitboard | weak_king_bitboard) & pawn_bitboard != 0
+    || (strong_to_move && pawn_attack_bitboard & weak_king_bitboard != 0){
+        return KPK_INVALID;
+    }
+
+    if strong_to_move{
+        let promotion_square = pawn_square - 8;
+
+        // promotes safely
+        if pawn_square / 8 == 1
+        && strong_king_square != promotion_square
+        && weak_king_square != promotion_square
+        && (KING_MOVE_MASK[weak_king_square] & (1 << promotion_square) == 0 || KING_MOVE_MASK[strong_king_square] & (1 << promotion_square) != 0){
+            return KPK_WIN;
+        }
+    }
+    else{
+        let weak_king_moves = KING_MOVE_MASK[weak_king_square] & !(KING_MOVE_MASK[strong_king_square] | pawn_attack_bitboard);
+
+        // stalemate
+        if weak_king_moves == 0{
+            return KPK_DRAW;
+        }
+
+        // takes the pawn
+        if weak_king_moves & pawn_bitboard != 0{
+            return KPK_DRAW;
+        }
+    }
+
+    return KPK_UNKNOWN;
+}
+
+// or of every position one move away
+fn get_kpk_successor_results(results: &[u8], strong_king_square: usize, pawn_square: usize, weak_king_square: usize, strong_to_move: bool) -> u8{
+    let mut successor_results: u8 = KPK_INVALID;
+
+    if strong_to_move{
+        let mut king_move_temp = KING_MOVE_MASK[strong_king_square];
+
+        while king_move_temp != 0{
+            let to_square = king_move_temp.trailing_zeros() as usize;
+
+            successor_results |= results[get_kpk_index(to_square, pawn_square, weak_king_square, false)];
+
+            king_move_temp ^= 1 << to_square;
+        }
+
+        let push_square = pawn_square - 8;
+        let king_bitboard: u64 = (1 << strong_king_square) | (1 << weak_king_square);
+
+        // promotions were decided at the start
+        if pawn_square / 8 > 1 && king_bitboard & (1 << push_square) == 0{
+            successor_results |= results[get_kpk_index(strong_king_square, push_square, weak_king_square, false)];
+
+            // double move from the 2nd rank
+            if pawn_square / 8 == 6 && king_bitboard & (1 << (push_square - 8)) == 0{
+                successor_results |= results[get_kpk_index(strong_king_square, push_square - 8, weak_king_square, false)];
+            }
+        }
+    }
+    else{
+        let mut king_move_temp = KING_MOVE_MASK[weak_king_square];
+
+        while king_move_temp != 0{
+            let to_square = king_move_temp.trailing_zeros() as usize;
+
+            successor_results |= results[get_kpk_index(strong_king_square, pawn_square, to_square, true)];
+
+            king_move_temp ^= 1 << to_square;
+        }
+    }
+
+    return successor_results;
+}
+
+fn generate_kpk_bitbase() -> Vec<u64>{
+    let mut results: Vec<u8> = vec![KPK_INVALID; KPK_POSITION_NUM];
+
+    for index in 0..KPK_POSITION_NUM{
+        let (strong_king_square, pawn_square, weak_king_square, strong_to_move) = get_kpk_squares(index);
+
+        results[index] = get_initial_kpk_result(strong_king_square, pawn_square, weak_king_square, strong_to_move);
+    }
+
+    // the strong side needs one winning move, the weak side one drawing move
+    // whatever is still unknown once nothing changes anymore can never be won
+    let mut changed = true;
+
+    while changed{
+        changed = false;
+
+        for index in 0..KPK_POSITION_NUM{
+            if results[index] != KPK_UNKNOWN{
+                continue;
+            }
+
+            let (strong_king_square, pawn_square, weak_king_square, strong_to_move) = get_kpk_squares(index);
+
+            let successor_results = get_kpk_successor_results(&results, strong_king_square, pawn_square, weak_king_square, strong_to_move);
+
+            let (good_result, bad_result) = if strong_to_move { (KPK_WIN, KPK_DRAW) } else { (KPK_DRAW, KPK_WIN) };
+
+            if successor_results & good_result != 0{
+                results[index] = good_result;
+                changed = true;
+            }
+            else if successor_results & KPK_UNKNOWN == 0{
+                results[index] = bad_result;
+                changed = true;
+            }
+        }
+    }
+
+    let mut bitbase: Vec<u64> = vec![0; KPK_POSITION_NUM / 64];
+
+    for index in 0..KPK_POSITION_NUM{
+        if results[index] == KPK_WIN{
+            bitbase[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    return bitbase;
+}
+
+// squares as if the strong side was white, true if the strong side wins
+pub fn probe_kpk_bitbase(mut strong_king_square: usize, mut pawn_square: usize, mut weak_king_square: usize, strong_to_move: bool) -> bool{
+    let kpk_bitbase = KPK_BITBASE.get_or_init(generate_kpk_bitbase);
+
+    // mirror onto the a to d files
+    if pawn_square % 8 >= 4{
+        strong_king_square ^= 7;
+        pawn_square ^= 7;
+        weak_king_square ^= 7;
+    }
+
+    let index = get_kpk_index(strong_king_square, pawn_square, weak_king_square, strong_to_move);
+
+    return kpk_bitbase[index / 64] & (1 << (index % 64)) != 0;
+}
+
+// None if the board isnt king and pawn vs king
+// otherwise whether the side with the pawn wins and which side that is
+pub fn probe_kpk_board(board: &ChessBoard) -> Option<(bool, bool)>{
+    if board.all_piece_bitboard.count_ones() != 3{
+        return None;
+    }
+
+    let strong_side: bool;
+
+    if board.piece_bitboards[0] != 0{
+        strong_side = true;
+    }
+    else if board.piece_bitboards[6] != 0{
+        strong_side = false;
+    }
+    else{
+        return None;
+    }
+
+    let mut squares: [usize; 3] = [
+        board.piece_bitboards[5].trailing_zeros() as usize,
+        board.piece_bitboards[0].trailing_zeros() as usize,
+        board.piece_bitboards[11].trailing_zeros() as usize,
+    ];
+
+    // black pawns get flipped so they move up the board
+    if !strong_side{
+        squares = [
+            board.piece_bitboards[11].trailing_zeros() as usize ^ 56,
+            board.piece_bitboards[6].trailing_zeros() as usize ^ 56,
+            board.piece_bitboards[5].trailing_zeros() as usize ^ 56,
+        ];
+    }
+
+    let is_win = probe_kpk_bitbase(squares[0], squares[1], squares[2], board.board_color == strong_side);
+
+    return Some((is_win, strong_side));
+}
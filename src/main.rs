@@ -17,6 +17,19 @@ use zobrist_hash::*;
 use timer::*;
 use transposition_table::*;
 use history_heuristic::*;
+use see::*;
+use uci::*;
+use search_tables::*;
+use skill::*;
+use search_stats::*;
+use eval_params::*;
+use eval_trace::*;
+use eval_score::*;
+use eval_accumulator::*;
+use pawn_hash_table::*;
+use endgame::*;
+use kpk_bitbase::*;
+use syzygy::*;
 
 mod board;
 mod move_compute;
@@ -28,6 +41,19 @@ mod game_board;
 mod zobrist_hash;
 mod transposition_table;
 mod history_heuristic;
+mod see;
+mod uci;
+mod search_tables;
+mod skill;
+mod search_stats;
+mod eval_params;
+mod eval_trace;
+mod eval_score;
+mod eval_accumulator;
+mod pawn_hash_table;
+mod endgame;
+mod kpk_bitbase;
+mod syzygy;
 
 mod app_settings;
 mod app_pieces;
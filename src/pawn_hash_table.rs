@@ -0,0 +1,67 @@
+use std::cell::RefCell;
+
+use crate::board::ChessBoard;
+use crate::evaluation::analyse_pawn_structure;
+
+// caches the pawn structure by the pawn hash, the pawns change in few moves so most lookups hit
+// entries only hold bitboards and counts, never scores, so changing the eval params doesnt make them stale
+
+pub const PAWN_HASH_TABLE_SIZE: usize = 1 << 14;
+
+// white then black
+// the all zero entry is the right one for a board without pawns (pawn hash 0)
+#[derive(Clone, Copy)]
+pub struct PawnStructure{
+    pub pawn_hash: u64,
+
+    pub passed_pawns: [u64; 2],
+    pub isolated_pawns: [u64; 2],
+    pub backward_pawns: [u64; 2],
+    // defended by a pawn or standing next to one
+    pub connected_pawns: [u64; 2],
+
+    // files with more than one pawn
+    pub doubled_file_num: [u8; 2],
+}
+
+pub const EMPTY_PAWN_STRUCTURE: PawnStructure = PawnStructure{
+    pawn_hash: 0,
+
+    passed_pawns: [0; 2],
+    isolated_pawns: [0; 2],
+    backward_pawns: [0; 2],
+    connected_pawns: [0; 2],
+
+    doubled_file_num: [0; 2],
+};
+
+pub struct PawnHashTable{
+    entries: Vec<PawnStructure>,
+}
+
+impl PawnHashTable{
+    pub fn new() -> PawnHashTable{
+        PawnHashTable{
+            entries: vec![EMPTY_PAWN_STRUCTURE; PAWN_HASH_TABLE_SIZE],
+        }
+    }
+
+    pub fn probe(&mut self, board: &ChessBoard) -> PawnStructure{
+        let index = (board.pawn_hash % PAWN_HASH_TABLE_SIZE as u64) as usize;
+
+        if self.entries[index].pawn_hash != board.pawn_hash{
+            self.entries[index] = analyse_pawn_structure(board);
+        }
+
+        return self.entries[index];
+    }
+}
+
+thread_local!{
+    // every search thread gets its own table, so the evaluation doesnt need a reference to one
+    static PAWN_HASH_TABLE: RefCell<PawnHashTable> = RefCell::new(PawnHashTable::new());
+}
+
+pub fn get_pawn_structure(board: &ChessBoard) -> PawnStructure{
+    return PAWN_HASH_TABLE.with(|pawn_hash_table| pawn_hash_table.borrow_mut().probe(board));
+}
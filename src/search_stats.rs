@@ -0,0 +1,120 @@
+// counters collected during a single search
+// they dont change what move gets played, they are just there to tune the search with
+
+#[derive(Clone)]
+pub struct SearchStats{
+    pub nodes: u64,
+    pub qnodes: u64,
+
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub tt_cutoffs: u64,
+
+    pub beta_cutoffs: u64,
+    // cutoffs by the first move searched, shows how good the move ordering is
+    pub first_move_cutoffs: u64,
+
+    pub reverse_futility_prunes: u64,
+    pub razoring_prunes: u64,
+    pub probcut_prunes: u64,
+    pub late_move_prunes: u64,
+    pub see_prunes: u64,
+    pub delta_prunes: u64,
+
+    pub lmr_reductions: u64,
+    // reduced searches that beat alpha and had to be searched again
+    pub lmr_researches: u64,
+    // null window searches that had to be searched again with the full window
+    pub pvs_researches: u64,
+
+    // positions found in the syzygy tablebases, root moves included
+    pub tb_hits: u64,
+
+    pub depth: u8,
+    // deepest ply reached, quiescence included
+    pub seldepth: u8,
+
+    // nodes used by every fully searched depth
+    pub depth_nodes: Vec<u64>,
+}
+
+impl SearchStats{
+    pub fn new() -> SearchStats{
+        SearchStats{
+            nodes: 0,
+            qnodes: 0,
+
+            tt_probes: 0,
+            tt_hits: 0,
+            tt_cutoffs: 0,
+
+            beta_cutoffs: 0,
+            first_move_cutoffs: 0,
+
+            reverse_futility_prunes: 0,
+            razoring_prunes: 0,
+            probcut_prunes: 0,
+            late_move_prunes: 0,
+            see_prunes: 0,
+            delta_prunes: 0,
+
+            lmr_reductions: 0,
+            lmr_researches: 0,
+            pvs_researches: 0,
+
+            tb_hits: 0,
+
+            depth: 0,
+            seldepth: 0,
+
+            depth_nodes: Vec::new(),
+        }
+    }
+
+    pub fn update_seldepth(&mut self, ply: u8){
+        if ply > self.seldepth{
+            self.seldepth = ply;
+        }
+    }
+
+    // how many times more nodes the last depth needed than the one before it
+    pub fn get_branching_factor(&self) -> f32{
+        let depth_num = self.depth_nodes.len();
+
+        if depth_num < 2 || self.depth_nodes[depth_num - 2] == 0{
+            return 0.0;
+        }
+
+        return self.depth_nodes[depth_num - 1] as f32 / self.depth_nodes[depth_num - 2] as f32;
+    }
+
+    // the branching factor a uniform tree of the same depth and size would have
+    pub fn get_effective_branching_factor(&self) -> f32{
+        if self.depth == 0 || self.nodes == 0{
+            return 0.0;
+        }
+
+        return (self.nodes as f32).powf(1.0 / self.depth as f32);
+    }
+
+    pub fn show(&self){
+        println!("depth: {} seldepth: {}", self.depth, self.seldepth);
+        println!("nodes: {} qnodes: {} ({:.1}% quiescence)", self.nodes, self.qnodes, get_percentage(self.qnodes, self.nodes + self.qnodes));
+        println!("branching factor: {:.2} effective: {:.2}", self.get_branching_factor(), self.get_effective_branching_factor());
+
+        println!("tt probes: {} hits: {} ({:.1}%) cutoffs: {} ({:.1}%)", self.tt_probes, self.tt_hits, get_percentage(self.tt_hits, self.tt_probes), self.tt_cutoffs, get_percentage(self.tt_cutoffs, self.tt_probes));
+        println!("beta cutoffs: {} on first move: {} ({:.1}%)", self.beta_cutoffs, self.first_move_cutoffs, get_percentage(self.first_move_cutoffs, self.beta_cutoffs));
+
+        println!("prunes - reverse futility: {} razoring: {} probcut: {} late move: {} see: {} delta: {}", self.reverse_futility_prunes, self.razoring_prunes, self.probcut_prunes, self.late_move_prunes, self.see_prunes, self.delta_prunes);
+        println!("lmr reductions: {} re-searches: {} pvs re-searches: {}", self.lmr_reductions, self.lmr_researches, self.pvs_researches);
+        println!("tb hits: {}", self.tb_hits);
+    }
+}
+
+fn get_percentage(part: u64, total: u64) -> f32{
+    if total == 0{
+        return 0.0;
+    }
+
+    return part as f32 * 100.0 / total as f32;
+}
@@ -0,0 +1,182 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+
+use crate::app_settings::*;
+use crate::zobrist_hash::*;
+use crate::transposition_table::*;
+use crate::history_heuristic::*;
+
+// saves the transposition table and history heuristic so long analysis can be resumed later
+
+// File Layout (little endian):
+// magic         4 bytes "CRST"
+// file version  u32
+// engine version  u8 length + bytes
+// hash scheme   u64
+// bucket num    u64
+// bucket size   u32
+// generation    u8
+// entries       key u16, best move u16, score i16, depth u8, info u8
+// history       [color][from][to] i16
+
+const FILE_MAGIC: &[u8; 4] = b"CRST";
+const FILE_VERSION: u32 = 1;
+
+// bump whenever the zobrist keys or the way entries are indexed/verified changes
+const TT_LAYOUT_VERSION: u64 = 1;
+
+// fingerprint of everything a stored hash depends on
+pub fn get_hash_scheme() -> u64{
+    let mut scheme: u64 = TT_LAYOUT_VERSION;
+
+    for key in zobrist_hash_table.iter().chain(REPETITION_COUNT_HASHES.iter()){
+        scheme = scheme.rotate_left(7) ^ key;
+    }
+
+    return scheme;
+}
+
+fn write_u8(writer: &mut impl Write, n: u8) -> io::Result<()>{
+    writer.write_all(&[n])
+}
+
+fn write_u16(writer: &mut impl Write, n: u16) -> io::Result<()>{
+    writer.write_all(&n.to_le_bytes())
+}
+
+fn write_u32(writer: &mut impl Write, n: u32) -> io::Result<()>{
+    writer.write_all(&n.to_le_bytes())
+}
+
+fn write_u64(writer: &mut impl Write, n: u64) -> io::Result<()>{
+    writer.write_all(&n.to_le_bytes())
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]>{
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+
+    return Ok(bytes);
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8>{
+    Ok(read_bytes::<1>(reader)?[0])
+}
+
+fn read_u16(reader: &mut impl Read) -> io::Result<u16>{
+    Ok(u16::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32>{
+    Ok(u32::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64>{
+    Ok(u64::from_le_bytes(read_bytes(reader)?))
+}
+
+fn invalid_data(message: String) -> io::Error{
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub fn save_search_tables(file_path: &str, transposition_table: &TranspositionTable, hh_table: &HistoryHueristicTable) -> io::Result<()>{
+    let mut writer = BufWriter::new(File::create(file_path)?);
+
+    // header
+    writer.write_all(FILE_MAGIC)?;
+    write_u32(&mut writer, FILE_VERSION)?;
+
+    write_u8(&mut writer, ENGINE_VERSION.len() as u8)?;
+    writer.write_all(ENGINE_VERSION.as_bytes())?;
+
+    write_u64(&mut writer, get_hash_scheme())?;
+    write_u64(&mut writer, transposition_table.table.len() as u64)?;
+    write_u32(&mut writer, BUCKET_SIZE as u32)?;
+    write_u8(&mut writer, transposition_table.generation)?;
+
+    for bucket in transposition_table.table.iter(){
+        for entry in bucket.entries.iter(){
+            write_u16(&mut writer, entry.key)?;
+            write_u16(&mut writer, entry.best_move)?;
+            write_u16(&mut writer, entry.score as u16)?;
+            write_u8(&mut writer, entry.depth)?;
+            write_u8(&mut writer, entry.info)?;
+        }
+    }
+
+    for color in 0..2{
+        for from in 0..64{
+            for to in 0..64{
+                write_u16(&mut writer, hh_table.hh_table[color][from][to] as u16)?;
+            }
+        }
+    }
+
+    writer.flush()
+}
+
+// the tables are only replaced once the whole file was read successfully
+pub fn load_search_tables(file_path: &str, transposition_table: &mut TranspositionTable, hh_table: &mut HistoryHueristicTable) -> io::Result<()>{
+    let mut reader = BufReader::new(File::open(file_path)?);
+
+    if &read_bytes::<4>(&mut reader)? != FILE_MAGIC{
+        return Err(invalid_data("not a search table file".to_string()));
+    }
+
+    let file_version = read_u32(&mut reader)?;
+
+    if file_version != FILE_VERSION{
+        return Err(invalid_data(format!("unsupported file version {} (expected {})", file_version, FILE_VERSION)));
+    }
+
+    let version_len = read_u8(&mut reader)? as usize;
+    let mut version_bytes = vec![0; version_len];
+    reader.read_exact(&mut version_bytes)?;
+
+    let engine_version = String::from_utf8_lossy(&version_bytes).to_string();
+
+    if engine_version != ENGINE_VERSION{
+        return Err(invalid_data(format!("saved by engine version {} (current {})", engine_version, ENGINE_VERSION)));
+    }
+
+    if read_u64(&mut reader)? != get_hash_scheme(){
+        return Err(invalid_data("saved with a different hash scheme".to_string()));
+    }
+
+    let bucket_num = read_u64(&mut reader)? as usize;
+    let bucket_size = read_u32(&mut reader)? as usize;
+    let generation = read_u8(&mut reader)?;
+
+    if bucket_size != BUCKET_SIZE || !bucket_num.is_power_of_two() || bucket_num > (MAX_HASH_SIZE_MB * 1024 * 1024) / size_of::<TTBucket>(){
+        return Err(invalid_data(format!("invalid table size ({} buckets of {})", bucket_num, bucket_size)));
+    }
+
+    let mut buckets = vec![TTBucket{entries: [TTEntry::null(); BUCKET_SIZE]}; bucket_num];
+
+    for bucket in buckets.iter_mut(){
+        for entry in bucket.entries.iter_mut(){
+            entry.key = read_u16(&mut reader)?;
+            entry.best_move = read_u16(&mut reader)?;
+            entry.score = read_u16(&mut reader)? as i16;
+            entry.depth = read_u8(&mut reader)?;
+            entry.info = read_u8(&mut reader)?;
+        }
+    }
+
+    let mut loaded_hh_table = HistoryHueristicTable::new();
+
+    for color in 0..2{
+        for from in 0..64{
+            for to in 0..64{
+                // a hand edited file could hold values the history updates never produce
+                loaded_hh_table.hh_table[color][from][to] = (read_u16(&mut reader)? as i16).clamp(-MAX_HISTORY, MAX_HISTORY);
+            }
+        }
+    }
+
+    *transposition_table = TranspositionTable::from_buckets(buckets, generation);
+    *hh_table = loaded_hh_table;
+
+    return Ok(());
+}
@@ -0,0 +1,138 @@
+use crate::move_compute::*;
+use crate::board::*;
+
+// Static Exchange Evaluation
+// plays out every capture on the target square (least valuable attacker first)
+// and checks whether the side to move ends up at least threshold ahead
+
+// pawn, bishop, knight, rook, queen, king
+pub const SEE_PIECE_VALUES: [i16; 6] = [100, 300, 300, 500, 900, 0];
+
+// cheapest attacker gets used first
+const SEE_ATTACKER_ORDER: [usize; 6] = [0, 2, 1, 3, 4, 5];
+
+// true if the exchange started by mv wins at least threshold
+pub fn see(board: &ChessBoard, mv: u16, threshold: i16) -> bool{
+    let special = get_move_info(mv);
+
+    // castling can never lose material
+    if special >= 9{
+        return threshold <= 0;
+    }
+
+    let from_square: usize = (mv & MOVE_DECODER_MASK) as usize;
+    let to_square: usize = ((mv >> 6) & MOVE_DECODER_MASK) as usize;
+
+    let mut occupied: u64 = board.all_piece_bitboard ^ (1 << from_square);
+
+    let captured_value: i16;
+
+    // en passant
+    if special == 3{
+        captured_value = SEE_PIECE_VALUES[0];
+
+        if board.board_color{
+            occupied ^= 1 << (to_square + 8);
+        }
+        else{
+            occupied ^= 1 << (to_square - 8);
+        }
+    }
+    else if board.piece_array[to_square] != 0{
+        captured_value = SEE_PIECE_VALUES[((board.piece_array[to_square] - 1) % 6) as usize];
+    }
+    else{
+        captured_value = 0;
+    }
+
+    let mut swap: i16 = captured_value - threshold;
+
+    // even winning the piece for free isnt enough
+    if swap < 0{
+        return false;
+    }
+
+    let moved_piece_type: usize = ((board.piece_array[from_square] - 1) % 6) as usize;
+
+    swap = SEE_PIECE_VALUES[moved_piece_type] - swap;
+
+    // even losing the moved piece still keeps us above the threshold
+    if swap <= 0{
+        return true;
+    }
+
+    occupied |= 1 << to_square;
+
+    let diagonal_sliders: u64 = board.piece_bitboards[1] | board.piece_bitboards[4] | board.piece_bitboards[7] | board.piece_bitboards[10];
+    let straight_sliders: u64 = board.piece_bitboards[3] | board.piece_bitboards[4] | board.piece_bitboards[9] | board.piece_bitboards[10];
+
+    let mut attackers: u64 = get_square_attackers(board, to_square, occupied);
+
+    let mut side_to_move: bool = board.board_color;
+    let mut result: bool = true;
+
+    loop{
+        side_to_move = !side_to_move;
+        attackers &= occupied;
+
+        let side_piece_offset: usize;
+        let side_pieces: u64;
+
+        if side_to_move{
+            side_piece_offset = 0;
+            side_pieces = board.white_piece_bitboard;
+        }
+        else{
+            side_piece_offset = 6;
+            side_pieces = board.black_piece_bitboard;
+        }
+
+        let side_attackers = attackers & side_pieces;
+
+        // the side to move has nothing left to recapture with
+        if side_attackers == 0{
+            break;
+        }
+
+        result = !result;
+
+        let mut attacker_type: usize = 5;
+        let mut attacker_bitboard: u64 = 0;
+
+        for piece_type in SEE_ATTACKER_ORDER{
+            attacker_bitboard = side_attackers & board.piece_bitboards[piece_type + side_piece_offset];
+
+            if attacker_bitboard != 0{
+                attacker_type = piece_type;
+                break;
+            }
+        }
+
+        // the king can only recapture if nothing defends the square anymore
+        if attacker_type == 5{
+            if attackers & !side_pieces != 0{
+                result = !result;
+            }
+            break;
+        }
+
+        swap = SEE_PIECE_VALUES[attacker_type] - swap;
+
+        if swap < result as i16{
+            break;
+        }
+
+        // remove the attacker and reveal the x-ray attackers behind it
+        occupied ^= attacker_bitboard & attacker_bitboard.wrapping_neg();
+
+        if attacker_type == 0 || attacker_type == 1 || attacker_type == 4{
+            attackers |= get_bishop_move_bitboard(to_square, occupied) & diagonal_sliders;
+        }
+
+        if attacker_type == 3 || attacker_type == 4{
+            attackers |= get_rook_move_bitboard(to_square, occupied) & straight_sliders;
+        }
+    }
+
+    return result;
+}
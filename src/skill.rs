@@ -0,0 +1,88 @@
+use rand::Rng;
+
+use crate::app_settings::*;
+use crate::engine::*;
+
+// Strength limiting
+// weaker levels search shallower, with fewer nodes, and pick among the best few root moves
+// with a random push towards the worse ones (similar to stockfish's skill level)
+
+pub const MAX_SKILL_LEVEL: u8 = 20;
+
+// number of root moves that get exact scores to pick from
+const SKILL_CANDIDATE_NUM: usize = 4;
+
+// largest score difference the random push is scaled by
+const SKILL_MAX_DELTA: i32 = 100;
+
+#[derive(Clone, Copy)]
+pub struct SkillLevel{
+    pub level: u8,
+}
+
+impl SkillLevel{
+    pub fn new(level: u8) -> SkillLevel{
+        SkillLevel{level: level.min(MAX_SKILL_LEVEL)}
+    }
+
+    pub fn is_full_strength(&self) -> bool{
+        return self.level >= MAX_SKILL_LEVEL;
+    }
+
+    // 1 ply at level 0 up to 10 plies at level 19
+    pub fn get_max_depth(&self) -> u8{
+        if self.is_full_strength(){
+            return MAX_SEARCH_DEPTH;
+        }
+
+        return 1 + self.level / 2;
+    }
+
+    // 0 means no limit
+    pub fn get_max_nodes(&self) -> u64{
+        if self.is_full_strength(){
+            return 0;
+        }
+
+        let level = self.level as u64 + 1;
+
+        return level * level * 2000;
+    }
+
+    pub fn get_candidate_num(&self) -> usize{
+        return SKILL_CANDIDATE_NUM;
+    }
+
+    // root_moves has to be sorted with the best move first
+    pub fn pick_move(&self, root_moves: &[MoveScorePair]) -> MoveScorePair{
+        let candidate_num = root_moves.len().min(self.get_candidate_num());
+
+        // found mates dont get thrown away
+        if candidate_num <= 1 || is_mate_score(root_moves[0].score){
+            return root_moves[0];
+        }
+
+        let mut rng = rand::thread_rng();
+
+        let top_score = root_moves[0].score as i32;
+        let weakness = 120 - 2 * self.level as i32;
+        let delta = (top_score - root_moves[candidate_num - 1].score as i32).min(SKILL_MAX_DELTA);
+
+        let mut best_index = 0;
+        let mut best_pushed_score = i32::MIN;
+
+        for i in 0..candidate_num{
+            let score = root_moves[i].score as i32;
+
+            // worse moves get pushed up more, and the push gets bigger the weaker we are
+            let push = (weakness * (top_score - score) + delta * rng.gen_range(0..weakness)) / 128;
+
+            if score + push > best_pushed_score{
+                best_pushed_score = score + push;
+                best_index = i;
+            }
+        }
+
+        return root_moves[best_index];
+    }
+}
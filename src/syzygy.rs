@@ -0,0 +1,1480 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+use crate::board::*;
+use crate::endgame::*;
+use crate::game_board::*;
+use crate::move_compute::*;
+
+use memmap2::Mmap;
+
+// syzygy win / draw / loss and distance to zeroing tablebases, ported from the stockfish probing code
+// the files get mapped and checked when the syzygy path gets set, only the parts that get probed are read
+// squares in here are the ones the files use, a1 = 0 and h8 = 63, ours get flipped with ^ 56
+
+// tablebase wins are below mates but above anything the evaluation gives out
+pub const TB_WIN_SCORE: i16 = 9000;
+
+// from the side to moves point of view
+// cursed wins and blessed losses are draws because of the fifty move rule
+pub const WDL_LOSS: i32 = -2;
+pub const WDL_BLESSED_LOSS: i32 = -1;
+pub const WDL_DRAW: i32 = 0;
+pub const WDL_CURSED_WIN: i32 = 1;
+pub const WDL_WIN: i32 = 2;
+
+const TB_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+const TB_FLAG_STM: u8 = 1;
+const TB_FLAG_MAPPED: u8 = 2;
+const TB_FLAG_WIN_PLIES: u8 = 4;
+const TB_FLAG_LOSS_PLIES: u8 = 8;
+const TB_FLAG_WIDE: u8 = 16;
+const TB_FLAG_SINGLE_VALUE: u8 = 128;
+
+// root moves get ranked from this down, moves that win before the fifty move rule are ranked above the bound
+const MAX_DTZ: i32 = 1 << 18;
+pub const TB_RANK_BOUND: i32 = MAX_DTZ / 2 - 100;
+
+// piece codes in the files by our piece index, pawn bishop knight rook queen king and black + 6
+const TB_PIECE_CODES: [u8; 12] = [1, 3, 2, 4, 5, 6, 9, 11, 10, 12, 13, 14];
+const TB_PIECE_CHARS: [u8; 7] = *b" PNBRQK";
+
+// which dtz map to use for each wdl result
+const WDL_TO_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+
+// 0 until tablebases are found, nothing gets probed above it
+static TB_MAX_CARDINALITY: AtomicUsize = AtomicUsize::new(0);
+
+static TABLEBASES: RwLock<Option<Tablebases>> = RwLock::new(None);
+static TB_INDEX_TABLES: OnceLock<TbIndexTables> = OnceLock::new();
+
+#[derive(Clone, Copy, PartialEq)]
+enum ProbeState{
+    Fail,
+    Ok,
+    // dtz tables only store one side to move
+    ChangeStm,
+    // the best move is a capture or pawn move, the stored dtz cant be used
+    ZeroingBestMove,
+}
+
+struct TbIndexTables{
+    map_b1h1h7: [u64; 64],
+    map_a1d1d4: [u64; 64],
+    map_kk: [[u64; 64]; 10],
+    binomial: [[u64; 64]; 6],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+    map_pawns: [usize; 64],
+}
+
+// the decompression info of one table, wdl files have one per side and per leading pawn file
+#[derive(Default)]
+struct PairsData{
+    flags: u8,
+    max_sym_len: u8,
+    min_sym_len: u8,
+    num_blocks: usize,
+    block_size: usize,
+    span: usize,
+    // offsets into the file
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u8>,
+    pieces: [u8; TB_PIECES],
+    group_idx: [u64; TB_PIECES + 1],
+    group_len: [usize; TB_PIECES + 1],
+    map_idx: [u16; 4],
+}
+
+struct TbData{
+    bytes: Mmap,
+    pairs: Vec<Vec<PairsData>>,
+    // dtz maps
+    map: usize,
+}
+
+impl TbData{
+    fn get(&self, stm: usize, file: usize) -> &PairsData{
+        return &self.pairs[stm % self.pairs.len()][file];
+    }
+}
+
+struct TbTable{
+    key: MaterialKey,
+    key2: MaterialKey,
+    piece_count: usize,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    // the leading side first, thats the side with fewer pawns
+    pawn_count: [usize; 2],
+
+    wdl: Option<TbData>,
+    dtz: Option<TbData>,
+}
+
+impl TbTable{
+    // "KRPvKR" - white pieces before the v, black ones after
+    fn new(code: &str) -> TbTable{
+        let signature = code.replace('v', "");
+        let (white_pieces, black_pieces) = code.split_once('v').unwrap();
+
+        let white_pawns = white_pieces.matches('P').count();
+        let black_pawns = black_pieces.matches('P').count();
+
+        let has_unique_pieces = [white_pieces, black_pieces].iter().any(|pieces| {
+            "PNBRQ".chars().any(|piece| pieces.matches(piece).count() == 1)
+        });
+
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+
+        return TbTable{
+            key: get_signature_material_key(&signature, true),
+            key2: get_signature_material_key(&signature, false),
+            piece_count: signature.len(),
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces,
+            pawn_count: if white_leads { [white_pawns, black_pawns] } else { [black_pawns, white_pawns] },
+
+            wdl: None,
+            dtz: None,
+        };
+    }
+}
+
+struct Tablebases{
+    tables: Vec<TbTable>,
+    // both material keys of a table point to it
+    table_index: HashMap<MaterialKey, usize>,
+}
+
+fn off_a1h8(square: usize) -> i64{
+    return (square >> 3) as i64 - (square & 7) as i64;
+}
+
+fn get_edge_distance(file: usize) -> usize{
+    return file.min(7 - file);
+}
+
+fn generate_index_tables() -> TbIndexTables{
+    let mut tables = TbIndexTables{
+        map_b1h1h7: [0; 64],
+        map_a1d1d4: [0; 64],
+        map_kk: [[0; 64]; 10],
+        binomial: [[0; 64]; 6],
+        lead_pawn_idx: [[0; 64]; 6],
+        lead_pawns_size: [[0; 4]; 6],
+        map_pawns: [0; 64],
+    };
+
+    // squares below the a1-h8 diagonal to 0..27
+    let mut code: u64 = 0;
+
+    for square in 0..64{
+        if off_a1h8(square) < 0{
+            tables.map_b1h1h7[square] = code;
+            code += 1;
+        }
+    }
+
+    // the a1-d1-d4 triangle to 0..9, diagonal squares last
+    let mut diagonal: Vec<usize> = Vec::new();
+    code = 0;
+
+    for square in [0, 1, 2, 3, 8, 9, 10, 11, 16, 17, 18, 19, 24, 25, 26, 27]{
+        if off_a1h8(square) < 0{
+            tables.map_a1d1d4[square] = code;
+            code += 1;
+        }
+        else if off_a1h8(square) == 0{
+            diagonal.push(square);
+        }
+    }
+
+    for square in diagonal{
+        tables.map_a1d1d4[square] = code;
+        code += 1;
+    }
+
+    // the 462 legal king pairs with the first king in the triangle
+    // if the first one is on the diagonal the second one cant be above it
+    let mut both_on_diagonal: Vec<(usize, usize)> = Vec::new();
+    code = 0;
+
+    for idx in 0..10{
+        for square_1 in 0..28{
+            // b1 is mapped to 0, the squares outside the triangle are as well
+            if tables.map_a1d1d4[square_1] != idx as u64 || (idx == 0 && square_1 != 1){
+                continue;
+            }
+
+            for square_2 in 0..64{
+                let file_distance = ((square_1 & 7) as i64 - (square_2 & 7) as i64).abs();
+                let rank_distance = ((square_1 >> 3) as i64 - (square_2 >> 3) as i64).abs();
+
+                if file_distance <= 1 && rank_distance <= 1{
+                    continue;
+                }
+                else if off_a1h8(square_1) == 0 && off_a1h8(square_2) > 0{
+                    continue;
+                }
+                else if off_a1h8(square_1) == 0 && off_a1h8(square_2) == 0{
+                    both_on_diagonal.push((idx, square_2));
+                }
+                else{
+                    tables.map_kk[idx][square_2] = code;
+                    code += 1;
+                }
+            }
+        }
+    }
+
+    for (idx, square) in both_on_diagonal{
+        tables.map_kk[idx][square] = code;
+        code += 1;
+    }
+
+    // ways to choose k out of n
+    tables.binomial[0][0] = 1;
+
+    for n in 1..64{
+        for k in 0..6.min(n + 1){
+            tables.binomial[k][n] = if k > 0 { tables.binomial[k - 1][n - 1] } else { 0 } + if k < n { tables.binomial[k][n - 1] } else { 0 };
+        }
+    }
+
+    // pawns from a2 to h7 to 0..47, the leading pawn is the one with the highest value
+    // thats the one closest to the edge and lowest on the board
+    let mut available_squares: i64 = 47;
+
+    for lead_pawns_count in 1..=5{
+        for file in 0..4{
+            let mut idx: u64 = 0;
+
+            for rank in 1..7{
+                let square = rank * 8 + file;
+
+                if lead_pawns_count == 1{
+                    tables.map_pawns[square] = available_squares as usize;
+                    available_squares -= 1;
+                    tables.map_pawns[square ^ 7] = available_squares as usize;
+                    available_squares -= 1;
+                }
+
+                tables.lead_pawn_idx[lead_pawns_count][square] = idx;
+                idx += tables.binomial[lead_pawns_count - 1][tables.map_pawns[square]];
+            }
+
+            tables.lead_pawns_size[lead_pawns_count][file] = idx;
+        }
+    }
+
+    return tables;
+}
+
+fn get_index_tables() -> &'static TbIndexTables{
+    return TB_INDEX_TABLES.get_or_init(generate_index_tables);
+}
+
+// every table with up to 7 pieces, like stockfish lists them
+fn get_tb_codes() -> Vec<String>{
+    let mut codes: Vec<String> = Vec::new();
+
+    let mut add = |pieces: &[usize]|{
+        let mut code = String::new();
+
+        for (i, &piece) in pieces.iter().enumerate(){
+            if i > 0 && piece == 6{
+                code.push('v');
+            }
+
+            code.push(TB_PIECE_CHARS[piece] as char);
+        }
+
+        codes.push(code);
+    };
+
+    for p1 in 1..6{
+        add(&[6, p1, 6]);
+
+        for p2 in 1..=p1{
+            add(&[6, p1, p2, 6]);
+            add(&[6, p1, 6, p2]);
+
+            for p3 in 1..6{
+                add(&[6, p1, p2, 6, p3]);
+            }
+
+            for p3 in 1..=p2{
+                add(&[6, p1, p2, p3, 6]);
+
+                for p4 in 1..=p3{
+                    add(&[6, p1, p2, p3, p4, 6]);
+
+                    for p5 in 1..=p4{
+                        add(&[6, p1, p2, p3, p4, p5, 6]);
+                    }
+
+                    for p5 in 1..6{
+                        add(&[6, p1, p2, p3, p4, 6, p5]);
+                    }
+                }
+
+                for p4 in 1..6{
+                    add(&[6, p1, p2, p3, 6, p4]);
+
+                    for p5 in 1..=p4{
+                        add(&[6, p1, p2, p3, 6, p4, p5]);
+                    }
+                }
+            }
+
+            for p3 in 1..=p1{
+                for p4 in 1..=(if p1 == p3 { p2 } else { p3 }){
+                    add(&[6, p1, p2, 6, p3, p4]);
+                }
+            }
+        }
+    }
+
+    return codes;
+}
+
+fn find_tb_file(folders: &Vec<&str>, file_name: &str) -> Option<PathBuf>{
+    for folder in folders{
+        let path = Path::new(folder).join(file_name);
+
+        if path.is_file(){
+            return Some(path);
+        }
+    }
+
+    return None;
+}
+
+// looks for tablebase files in every folder of the path, separated by ':' (';' on windows)
+// an empty path turns probing off, returns how many wdl and dtz files were loaded
+pub fn init_tablebases(path: &str) -> (usize, usize){
+    TB_MAX_CARDINALITY.store(0, Ordering::Relaxed);
+
+    let mut tablebases_lock = TABLEBASES.write().unwrap();
+    *tablebases_lock = None;
+
+    if path.is_empty() || path == "<empty>"{
+        return (0, 0);
+    }
+
+    let separator = if cfg!(windows) { ';' } else { ':' };
+    let folders: Vec<&str> = path.split(separator).filter(|folder| !folder.is_empty()).collect();
+
+    let mut tablebases = Tablebases{tables: Vec::new(), table_index: HashMap::new()};
+
+    let mut max_cardinality = 0;
+    let mut dtz_count = 0;
+
+    for code in get_tb_codes(){
+        let wdl_path = match find_tb_file(&folders, &format!("{}.rtbw", code)){
+            Some(wdl_path) => wdl_path,
+            None => continue,
+        };
+
+        let mut table = TbTable::new(&code);
+
+        table.wdl = load_tb_data(&table, &wdl_path, true);
+
+        if table.wdl.is_none(){
+            continue;
+        }
+
+        if let Some(dtz_path) = find_tb_file(&folders, &format!("{}.rtbz", code)){
+            table.dtz = load_tb_data(&table, &dtz_path, false);
+        }
+
+        if table.dtz.is_some(){
+            dtz_count += 1;
+        }
+
+        max_cardinality = max_cardinality.max(table.piece_count);
+
+        tablebases.table_index.insert(table.key, tablebases.tables.len());
+        tablebases.table_index.insert(table.key2, tablebases.tables.len());
+        tablebases.tables.push(table);
+    }
+
+    let wdl_count = tablebases.tables.len();
+
+    if wdl_count != 0{
+        *tablebases_lock = Some(tablebases);
+    }
+
+    TB_MAX_CARDINALITY.store(max_cardinality, Ordering::Relaxed);
+
+    return (wdl_count, dtz_count);
+}
+
+// the most pieces a found table has, 0 when there are none
+pub fn get_tb_max_cardinality() -> usize{
+    return TB_MAX_CARDINALITY.load(Ordering::Relaxed);
+}
+
+// castling rights arent in the tables
+pub fn can_probe_tablebases(board: &ChessBoard) -> bool{
+    return board.all_piece_bitboard.count_ones() as usize <= get_tb_max_cardinality() && board.board_info & 15 == 0;
+}
+
+// everything read from the files is bounds checked, a corrupt file fails the probe instead of panicking
+fn read_u8(bytes: &[u8], offset: usize) -> Option<u8>{
+    return bytes.get(offset).copied();
+}
+
+fn read_u16_le(bytes: &[u8], offset: usize) -> Option<u16>{
+    let value_bytes = bytes.get(offset..offset.checked_add(2)?)?;
+
+    return Some(u16::from_le_bytes([value_bytes[0], value_bytes[1]]));
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32>{
+    let value_bytes = bytes.get(offset..offset.checked_add(4)?)?;
+
+    return Some(u32::from_le_bytes([value_bytes[0], value_bytes[1], value_bytes[2], value_bytes[3]]));
+}
+
+// the last block can be read past the end of the file
+fn read_u32_be(bytes: &[u8], offset: usize) -> u32{
+    let mut value: u32 = 0;
+
+    for i in 0..4{
+        value = value << 8 | *bytes.get(offset + i).unwrap_or(&0) as u32;
+    }
+
+    return value;
+}
+
+// left and right child of a symbol, 12 bits each
+fn get_btree_pair(bytes: &[u8], btree: usize, sym: usize) -> Option<(usize, usize)>{
+    let offset = btree.checked_add(sym.checked_mul(3)?)?;
+    let pair_bytes = bytes.get(offset..offset.checked_add(3)?)?;
+
+    let left = ((pair_bytes[1] as usize & 0xF) << 8) | pair_bytes[0] as usize;
+    let right = ((pair_bytes[2] as usize) << 4) | (pair_bytes[1] as usize >> 4);
+
+    return Some((left, right));
+}
+
+// splits the pieces into groups that get encoded together and works out what each group gets multiplied by
+fn set_groups(table: &TbTable, d: &mut PairsData, order: [usize; 2], file: usize) -> Option<()>{
+    let index_tables = get_index_tables();
+
+    let mut n = 0;
+    let mut first_len: i64 = if table.has_pawns { 0 } else if table.has_unique_pieces { 3 } else { 2 };
+
+    d.group_len[n] = 1;
+
+    for i in 1..table.piece_count{
+        first_len -= 1;
+
+        if first_len > 0 || d.pieces[i] == d.pieces[i - 1]{
+            d.group_len[n] += 1;
+        }
+        else{
+            n += 1;
+            d.group_len[n] = 1;
+        }
+    }
+
+    n += 1;
+    d.group_len[n] = 0;
+
+    // pawns on both sides
+    let pp = table.has_pawns && table.pawn_count[1] != 0;
+
+    let mut next = if pp { 2 } else { 1 };
+    let mut free_squares = 64 - d.group_len[0] - if pp { d.group_len[1] } else { 0 };
+    let mut idx: u64 = 1;
+
+    let mut k = 0;
+
+    while next < n || k == order[0] || k == order[1]{
+        // leading pawns or pieces
+        if k == order[0]{
+            d.group_idx[0] = idx;
+
+            if table.has_pawns{
+                idx *= index_tables.lead_pawns_size.get(d.group_len[0])?[file];
+            }
+            else if table.has_unique_pieces{
+                idx *= 31332;
+            }
+            else{
+                idx *= 462;
+            }
+        }
+        // remaining pawns
+        else if k == order[1]{
+            d.group_idx[1] = idx;
+            idx *= index_tables.binomial.get(d.group_len[1])?[48 - d.group_len[0]];
+        }
+        // remaining pieces
+        else{
+            let group_len = *d.group_len.get(next)?;
+
+            *d.group_idx.get_mut(next)? = idx;
+            idx *= index_tables.binomial.get(group_len)?[free_squares];
+            free_squares -= group_len;
+            next += 1;
+        }
+
+        k += 1;
+    }
+
+    d.group_idx[n] = idx;
+
+    return Some(());
+}
+
+// how many values each symbol stands for - 1
+fn set_symlen(d: &mut PairsData, bytes: &[u8], sym: usize, visited: &mut Vec<bool>) -> Option<u8>{
+    visited[sym] = true;
+
+    let (left, right) = get_btree_pair(bytes, d.btree, sym)?;
+
+    if right == 0xFFF{
+        return Some(0);
+    }
+
+    if left >= visited.len() || right >= visited.len(){
+        return None;
+    }
+
+    if !visited[left]{
+        d.symlen[left] = set_symlen(d, bytes, left, visited)?;
+    }
+
+    if !visited[right]{
+        d.symlen[right] = set_symlen(d, bytes, right, visited)?;
+    }
+
+    return Some(d.symlen[left].wrapping_add(d.symlen[right]).wrapping_add(1));
+}
+
+fn set_sizes(d: &mut PairsData, bytes: &[u8], mut data: usize) -> Option<usize>{
+    d.flags = read_u8(bytes, data)?;
+    data += 1;
+
+    // every position has the same value, stored in min_sym_len
+    if d.flags & TB_FLAG_SINGLE_VALUE != 0{
+        d.min_sym_len = read_u8(bytes, data)?;
+        return Some(data + 1);
+    }
+
+    let group_end = d.group_len.iter().position(|&group_len| group_len == 0).unwrap_or(TB_PIECES);
+    let tb_size = d.group_idx[group_end];
+
+    d.block_size = 1usize.checked_shl(read_u8(bytes, data)? as u32)?;
+    d.span = 1usize.checked_shl(read_u8(bytes, data + 1)? as u32)?;
+    d.sparse_index_size = ((tb_size + d.span as u64 - 1) / d.span as u64) as usize;
+
+    let padding = read_u8(bytes, data + 2)? as usize;
+
+    d.num_blocks = read_u32_le(bytes, data + 3)? as usize;
+    d.block_length_size = d.num_blocks + padding;
+
+    d.max_sym_len = read_u8(bytes, data + 7)?;
+    d.min_sym_len = read_u8(bytes, data + 8)?;
+
+    data += 9;
+
+    d.lowest_sym = data;
+
+    // canonical huffman codes, longer symbols have lower values
+    // base64 holds the lowest code of every length padded to 64 bits
+    let base64_size = d.max_sym_len.checked_sub(d.min_sym_len)? as usize + 1;
+
+    d.base64 = vec![0; base64_size];
+
+    for i in (0..base64_size - 1).rev(){
+        let code_count = read_u16_le(bytes, d.lowest_sym + i * 2)? as u64;
+        let next_code_count = read_u16_le(bytes, d.lowest_sym + (i + 1) * 2)? as u64;
+
+        d.base64[i] = (d.base64[i + 1] + code_count).checked_sub(next_code_count)? / 2;
+    }
+
+    for i in 0..base64_size{
+        let shift = 64u32.checked_sub(i as u32 + d.min_sym_len as u32)?;
+
+        d.base64[i] = d.base64[i].checked_shl(shift).unwrap_or(0);
+    }
+
+    data += base64_size * 2;
+
+    let symlen_size = read_u16_le(bytes, data)? as usize;
+    data += 2;
+
+    d.btree = data;
+    d.symlen = vec![0; symlen_size];
+
+    let mut visited = vec![false; symlen_size];
+
+    for sym in 0..symlen_size{
+        if !visited[sym]{
+            d.symlen[sym] = set_symlen(d, bytes, sym, &mut visited)?;
+        }
+    }
+
+    return Some(data + symlen_size * 3 + (symlen_size & 1));
+}
+
+// dtz tables map their values per wdl result, returns where the maps start
+fn set_dtz_map(pairs: &mut Vec<Vec<PairsData>>, bytes: &[u8], mut data: usize, max_file: usize) -> Option<(usize, usize)>{
+    let map = data;
+
+    for file in 0..=max_file{
+        let flags = pairs[0][file].flags;
+
+        if flags & TB_FLAG_MAPPED == 0{
+            continue;
+        }
+
+        if flags & TB_FLAG_WIDE != 0{
+            data += data & 1;
+
+            for i in 0..4{
+                pairs[0][file].map_idx[i] = ((data - map) / 2 + 1) as u16;
+                data += 2 * read_u16_le(bytes, data)? as usize + 2;
+            }
+        }
+        else{
+            for i in 0..4{
+                pairs[0][file].map_idx[i] = (data - map + 1) as u16;
+                data += read_u8(bytes, data)? as usize + 1;
+            }
+        }
+    }
+
+    return Some((map, data + (data & 1)));
+}
+
+// reads the layout of every table in the file, returns the dtz map offset
+// None if any part of the layout lies outside of the file
+fn init_tb_pairs(table: &TbTable, bytes: &[u8], pairs: &mut Vec<Vec<PairsData>>, is_wdl: bool) -> Option<usize>{
+    // the first byte after the magic holds flags
+    let mut data = 5;
+
+    let sides = if is_wdl && table.key != table.key2 { 2 } else { 1 };
+    let max_file = if table.has_pawns { 3 } else { 0 };
+
+    let pp = table.has_pawns && table.pawn_count[1] != 0;
+
+    for file in 0..=max_file{
+        let order_byte = read_u8(bytes, data)?;
+        let pawn_order_byte = if pp { read_u8(bytes, data + 1)? } else { 0xFF };
+
+        let order = [
+            [(order_byte & 0xF) as usize, (pawn_order_byte & 0xF) as usize],
+            [(order_byte >> 4) as usize, (pawn_order_byte >> 4) as usize],
+        ];
+
+        data += 1 + pp as usize;
+
+        for k in 0..table.piece_count{
+            let piece_byte = read_u8(bytes, data)?;
+
+            for i in 0..sides{
+                pairs[i][file].pieces[k] = if i != 0 { piece_byte >> 4 } else { piece_byte & 0xF };
+            }
+
+            data += 1;
+        }
+
+        for i in 0..sides{
+            set_groups(table, &mut pairs[i][file], order[i], file)?;
+        }
+    }
+
+    data += data & 1;
+
+    for file in 0..=max_file{
+        for i in 0..sides{
+            data = set_sizes(&mut pairs[i][file], bytes, data)?;
+        }
+    }
+
+    let mut map = 0;
+
+    if !is_wdl{
+        (map, data) = set_dtz_map(pairs, bytes, data, max_file)?;
+    }
+
+    for file in 0..=max_file{
+        for i in 0..sides{
+            pairs[i][file].sparse_index = data;
+            data = data.checked_add(pairs[i][file].sparse_index_size.checked_mul(6)?)?;
+        }
+    }
+
+    for file in 0..=max_file{
+        for i in 0..sides{
+            pairs[i][file].block_length = data;
+            data = data.checked_add(pairs[i][file].block_length_size.checked_mul(2)?)?;
+        }
+    }
+
+    for file in 0..=max_file{
+        for i in 0..sides{
+            data = data.checked_add(0x3F)? & !0x3F;
+            pairs[i][file].data = data;
+            data = data.checked_add(pairs[i][file].num_blocks.checked_mul(pairs[i][file].block_size)?)?;
+        }
+    }
+
+    if data > bytes.len(){
+        return None;
+    }
+
+    return Some(map);
+}
+
+fn load_tb_data(table: &TbTable, path: &Path, is_wdl: bool) -> Option<TbData>{
+    let file = File::open(path).ok()?;
+
+    // the os pages the file in as it gets probed
+    // the files must not be changed while they are mapped
+    let bytes = unsafe { Mmap::map(&file) }.ok()?;
+
+    let magic = if is_wdl { WDL_MAGIC } else { DTZ_MAGIC };
+
+    // pawns and split flags have to match the file name
+    if bytes.len() % 64 != 16 || bytes[0..4] != magic
+    || (bytes[4] & 2 != 0) != table.has_pawns
+    || (is_wdl && (bytes[4] & 1 != 0) != (table.key != table.key2)){
+        println!("corrupt tablebase file {}", path.display());
+        return None;
+    }
+
+    let sides = if is_wdl { 2 } else { 1 };
+    let mut pairs: Vec<Vec<PairsData>> = (0..sides).map(|_| (0..4).map(|_| PairsData::default()).collect()).collect();
+
+    let map = match init_tb_pairs(table, &bytes, &mut pairs, is_wdl){
+        Some(map) => map,
+        None => {
+            println!("corrupt tablebase file {}", path.display());
+            return None;
+        },
+    };
+
+    return Some(TbData{bytes, pairs, map});
+}
+
+fn decompress_pairs(d: &PairsData, bytes: &[u8], idx: u64) -> Option<i32>{
+    if d.flags & TB_FLAG_SINGLE_VALUE != 0{
+        return Some(d.min_sym_len as i32);
+    }
+
+    // the sparse index points to the block of every span-th value
+    let k = (idx / d.span as u64) as usize;
+
+    if k >= d.sparse_index_size{
+        return None;
+    }
+
+    let mut block = read_u32_le(bytes, d.sparse_index + k * 6)? as usize;
+    let mut offset = read_u16_le(bytes, d.sparse_index + k * 6 + 4)? as i64;
+
+    offset += (idx % d.span as u64) as i64 - (d.span / 2) as i64;
+
+    // every block stores block length + 1 values
+    while offset < 0{
+        block = block.checked_sub(1)?;
+        offset += read_u16_le(bytes, d.block_length + block * 2)? as i64 + 1;
+    }
+
+    while offset > read_u16_le(bytes, d.block_length + block * 2)? as i64{
+        offset -= read_u16_le(bytes, d.block_length + block * 2)? as i64 + 1;
+        block += 1;
+    }
+
+    if block >= d.num_blocks{
+        return None;
+    }
+
+    let mut ptr = d.data + block * d.block_size;
+
+    let mut buf64 = (read_u32_be(bytes, ptr) as u64) << 32 | read_u32_be(bytes, ptr + 4) as u64;
+    let mut buf64_size: usize = 64;
+
+    ptr += 8;
+
+    let mut sym: usize;
+
+    loop{
+        // symbol length - min_sym_len
+        let mut len = 0;
+
+        while buf64 < *d.base64.get(len)?{
+            len += 1;
+        }
+
+        let shift = 64usize.checked_sub(len + d.min_sym_len as usize)?;
+
+        sym = (buf64 - d.base64[len]).checked_shr(shift as u32).unwrap_or(0) as usize;
+        sym += read_u16_le(bytes, d.lowest_sym + len * 2)? as usize;
+
+        let sym_len = *d.symlen.get(sym)? as i64;
+
+        if offset < sym_len + 1{
+            break;
+        }
+
+        offset -= sym_len + 1;
+
+        len += d.min_sym_len as usize;
+        buf64 = buf64.checked_shl(len as u32).unwrap_or(0);
+        buf64_size = buf64_size.checked_sub(len)?;
+
+        if buf64_size <= 32{
+            buf64_size += 32;
+            buf64 |= (read_u32_be(bytes, ptr) as u64) << (64 - buf64_size);
+            ptr += 4;
+        }
+    }
+
+    // the symbol expands into pairs of symbols, go down until the value at the offset
+    // a tree can't be deeper than it has symbols, a corrupt one could loop forever
+    for _ in 0..d.symlen.len(){
+        if d.symlen[sym] == 0{
+            return Some(get_btree_pair(bytes, d.btree, sym)?.0 as i32);
+        }
+
+        let (left, right) = get_btree_pair(bytes, d.btree, sym)?;
+        let left_len = *d.symlen.get(left)? as i64;
+
+        if offset < left_len + 1{
+            sym = left;
+        }
+        else{
+            offset -= left_len + 1;
+            sym = right;
+        }
+
+        if sym >= d.symlen.len(){
+            return None;
+        }
+    }
+
+    return None;
+}
+
+fn map_score(tb_data: &TbData, file: usize, mut value: i32, wdl: i32, is_wdl: bool) -> Option<i32>{
+    if is_wdl{
+        return if (0..=4).contains(&value) { Some(value - 2) } else { None };
+    }
+
+    let d = tb_data.get(0, file);
+
+    if d.flags & TB_FLAG_MAPPED != 0{
+        let map_index = d.map_idx[WDL_TO_MAP[(wdl + 2) as usize]] as usize + value as usize;
+
+        if d.flags & TB_FLAG_WIDE != 0{
+            value = read_u16_le(&tb_data.bytes, tb_data.map + map_index * 2)? as i32;
+        }
+        else{
+            value = read_u8(&tb_data.bytes, tb_data.map + map_index)? as i32;
+        }
+    }
+
+    // stored in moves unless the flags say plies
+    if (wdl == WDL_WIN && d.flags & TB_FLAG_WIN_PLIES == 0)
+    || (wdl == WDL_LOSS && d.flags & TB_FLAG_LOSS_PLIES == 0)
+    || wdl == WDL_CURSED_WIN || wdl == WDL_BLESSED_LOSS{
+        value *= 2;
+    }
+
+    return Some(value + 1);
+}
+
+fn do_probe_table(board: &ChessBoard, table: &TbTable, tb_data: &TbData, is_wdl: bool, wdl: i32, state: &mut ProbeState) -> i32{
+    let index_tables = get_index_tables();
+
+    let mut squares: [usize; TB_PIECES] = [0; TB_PIECES];
+    let mut pieces: [u8; TB_PIECES] = [0; TB_PIECES];
+
+    let mut size = 0;
+    let mut lead_pawns_count = 0;
+    let mut lead_pawns: u64 = 0;
+    let mut tb_file = 0;
+
+    // the tables are stored with white as the stronger side
+    // symmetric tables only store white to move
+    let black_to_move = !board.board_color;
+    let symmetric_black_to_move = table.key == table.key2 && black_to_move;
+    let black_stronger = get_material_key(board) != table.key;
+
+    let flip = symmetric_black_to_move || black_stronger;
+    let flip_color: u8 = if flip { 8 } else { 0 };
+    let flip_squares: usize = if flip { 56 } else { 0 };
+    let stm = (flip ^ black_to_move) as usize;
+
+    // our bitboards have a8 as bit 0
+    let all_pieces = board.all_piece_bitboard.swap_bytes();
+
+    // pawn tables are split by the file of the leading pawn
+    if table.has_pawns{
+        let lead_piece = tb_data.get(0, 0).pieces[0] ^ flip_color;
+        let lead_pawn_index = if lead_piece < 8 { 0 } else { 6 };
+
+        lead_pawns = board.piece_bitboards[lead_pawn_index].swap_bytes();
+
+        let mut pawns_temp = lead_pawns;
+
+        while pawns_temp != 0{
+            squares[size] = pawns_temp.trailing_zeros() as usize ^ flip_squares;
+            size += 1;
+
+            pawns_temp &= pawns_temp - 1;
+        }
+
+        lead_pawns_count = size;
+
+        // the file names a leading piece we dont have
+        if lead_pawns_count == 0{
+            *state = ProbeState::Fail;
+            return 0;
+        }
+
+        let mut lead_index = 0;
+
+        for i in 1..lead_pawns_count{
+            if index_tables.map_pawns[squares[i]] > index_tables.map_pawns[squares[lead_index]]{
+                lead_index = i;
+            }
+        }
+
+        squares.swap(0, lead_index);
+
+        tb_file = get_edge_distance(squares[0] & 7);
+    }
+
+    if !is_wdl{
+        let flags = tb_data.get(stm, tb_file).flags;
+
+        if (flags & TB_FLAG_STM) as usize != stm && !(table.key == table.key2 && !table.has_pawns){
+            *state = ProbeState::ChangeStm;
+            return 0;
+        }
+    }
+
+    let mut pieces_temp = all_pieces ^ lead_pawns;
+
+    while pieces_temp != 0{
+        let square = pieces_temp.trailing_zeros() as usize;
+
+        squares[size] = square ^ flip_squares;
+        pieces[size] = TB_PIECE_CODES[board.piece_array[square ^ 56] as usize - 1] ^ flip_color;
+        size += 1;
+
+        pieces_temp &= pieces_temp - 1;
+    }
+
+    let d = tb_data.get(stm, tb_file);
+
+    // same piece order as the table
+    for i in lead_pawns_count..size - 1{
+        for j in i + 1..size{
+            if d.pieces[i] == pieces[j]{
+                pieces.swap(i, j);
+                squares.swap(i, j);
+                break;
+            }
+        }
+    }
+
+    // the leading piece goes into the a1-d1-d4 triangle
+    if squares[0] & 7 > 3{
+        for i in 0..size{
+            squares[i] ^= 7;
+        }
+    }
+
+    let mut idx: u64;
+
+    if table.has_pawns{
+        idx = index_tables.lead_pawn_idx[lead_pawns_count][squares[0]];
+
+        squares[1..lead_pawns_count].sort_by_key(|&square| index_tables.map_pawns[square]);
+
+        for i in 1..lead_pawns_count{
+            idx += index_tables.binomial[i][index_tables.map_pawns[squares[i]]];
+        }
+    }
+    else{
+        if squares[0] >> 3 > 3{
+            for i in 0..size{
+                squares[i] ^= 56;
+            }
+        }
+
+        // the first piece of the leading group off the diagonal goes below it
+        for i in 0..d.group_len[0]{
+            if off_a1h8(squares[i]) == 0{
+                continue;
+            }
+
+            if off_a1h8(squares[i]) > 0{
+                for j in i..size{
+                    squares[j] = ((squares[j] >> 3) | (squares[j] << 3)) & 63;
+                }
+            }
+
+            break;
+        }
+
+        // at least 3 unique pieces, kings included, get encoded together
+        if table.has_unique_pieces{
+            let square_0 = squares[0] as u64;
+            let square_1 = squares[1] as u64;
+            let square_2 = squares[2] as u64;
+
+            let adjust_1 = (square_1 > square_0) as u64;
+            let adjust_2 = (square_2 > square_0) as u64 + (square_2 > square_1) as u64;
+
+            if off_a1h8(squares[0]) != 0{
+                idx = (index_tables.map_a1d1d4[squares[0]] * 63 + (square_1 - adjust_1)) * 62 + square_2 - adjust_2;
+            }
+            else if off_a1h8(squares[1]) != 0{
+                idx = (6 * 63 + (square_0 >> 3) * 28 + index_tables.map_b1h1h7[squares[1]]) * 62 + square_2 - adjust_2;
+            }
+            else if off_a1h8(squares[2]) != 0{
+                idx = 6 * 63 * 62 + 4 * 28 * 62 + (square_0 >> 3) * 7 * 28 + ((square_1 >> 3) - adjust_1) * 28 + index_tables.map_b1h1h7[squares[2]];
+            }
+            else{
+                idx = 6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + (square_0 >> 3) * 7 * 6 + ((square_1 >> 3) - adjust_1) * 6 + ((square_2 >> 3) - adjust_2);
+            }
+        }
+        else{
+            idx = index_tables.map_kk[index_tables.map_a1d1d4[squares[0]] as usize][squares[1]];
+        }
+    }
+
+    idx *= d.group_idx[0];
+
+    // the remaining pawns and then pieces, each group sorted by square
+    let mut group_start = d.group_len[0];
+    let mut remaining_pawns = table.has_pawns && table.pawn_count[1] != 0;
+    let mut next = 1;
+
+    while d.group_len[next] != 0{
+        let group_len = d.group_len[next];
+
+        squares[group_start..group_start + group_len].sort();
+
+        let mut n: u64 = 0;
+
+        for i in 0..group_len{
+            let square = squares[group_start + i];
+            let adjust = squares[0..group_start].iter().filter(|&&other_square| square > other_square).count();
+
+            let square_index = match (square - adjust).checked_sub(if remaining_pawns { 8 } else { 0 }){
+                Some(square_index) => square_index,
+                None => {
+                    *state = ProbeState::Fail;
+                    return 0;
+                },
+            };
+
+            n += index_tables.binomial[i + 1][square_index];
+        }
+
+        remaining_pawns = false;
+        idx += n * d.group_idx[next];
+        group_start += group_len;
+        next += 1;
+    }
+
+    return match decompress_pairs(d, &tb_data.bytes, idx).and_then(|value| map_score(tb_data, tb_file, value, wdl, is_wdl)){
+        Some(score) => score,
+        None => {
+            *state = ProbeState::Fail;
+            0
+        },
+    };
+}
+
+fn probe_table(board: &ChessBoard, is_wdl: bool, wdl: i32, state: &mut ProbeState) -> i32{
+    // only the kings
+    if board.all_piece_bitboard.count_ones() == 2{
+        return WDL_DRAW;
+    }
+
+    let tablebases_lock = TABLEBASES.read().unwrap();
+
+    let tablebases = match tablebases_lock.as_ref(){
+        Some(tablebases) => tablebases,
+        None => {
+            *state = ProbeState::Fail;
+            return 0;
+        },
+    };
+
+    let table = match tablebases.table_index.get(&get_material_key(board)){
+        Some(&table_index) => &tablebases.tables[table_index],
+        None => {
+            *state = ProbeState::Fail;
+            return 0;
+        },
+    };
+
+    let tb_data = if is_wdl { &table.wdl } else { &table.dtz };
+
+    return match tb_data{
+        Some(tb_data) => do_probe_table(board, table, tb_data, is_wdl, wdl, state),
+        None => {
+            *state = ProbeState::Fail;
+            0
+        },
+    };
+}
+
+fn get_legal_moves(board: &mut ChessBoard) -> Vec<u16>{
+    let mut move_buffer = MoveBuffer::new();
+
+    get_moves(board, &mut move_buffer);
+
+    return Vec::from(&move_buffer.mv_arr[0..move_buffer.index]);
+}
+
+// en passant included
+fn is_tb_capture(board: &ChessBoard, mv: u16) -> bool{
+    return is_capture(mv, board) || mv >> 12 == 3;
+}
+
+fn is_pawn_move(board: &ChessBoard, mv: u16) -> bool{
+    return board.piece_array[(mv & MOVE_DECODER_MASK) as usize] % 6 == 1;
+}
+
+fn is_checkmate(board: &mut ChessBoard) -> bool{
+    update_board(board);
+
+    return board.check_mask != 0 && get_legal_moves(board).len() == 0;
+}
+
+// the tables dont know about en passant, so captures get searched first
+// with check_zeroing_moves pawn moves get searched as well, dtz cant be trusted when one of them is the best move
+fn search_wdl(board: &mut ChessBoard, check_zeroing_moves: bool, state: &mut ProbeState) -> i32{
+    let moves = get_legal_moves(board);
+
+    let mut best_value = WDL_LOSS;
+    let mut move_count = 0;
+
+    for &mv in &moves{
+        if !is_tb_capture(board, mv) && (!check_zeroing_moves || !is_pawn_move(board, mv)){
+            continue;
+        }
+
+        move_count += 1;
+
+        let mut sub_board = board.clone();
+        make_move(&mut sub_board, mv);
+
+        let value = -search_wdl(&mut sub_board, false, state);
+
+        if *state == ProbeState::Fail{
+            return WDL_DRAW;
+        }
+
+        if value > best_value{
+            best_value = value;
+
+            if value >= WDL_WIN{
+                *state = ProbeState::ZeroingBestMove;
+                return value;
+            }
+        }
+    }
+
+    // every legal move was searched, the table might be wrong because of en passant
+    let no_more_moves = move_count != 0 && move_count == moves.len();
+
+    let value: i32;
+
+    if no_more_moves{
+        value = best_value;
+    }
+    else{
+        value = probe_table(board, true, WDL_DRAW, state);
+
+        if *state == ProbeState::Fail{
+            return WDL_DRAW;
+        }
+    }
+
+    if best_value >= value{
+        *state = if best_value > WDL_DRAW || no_more_moves { ProbeState::ZeroingBestMove } else { ProbeState::Ok };
+        return best_value;
+    }
+
+    *state = ProbeState::Ok;
+    return value;
+}
+
+fn get_dtz_before_zeroing(wdl: i32) -> i32{
+    return match wdl{
+        WDL_WIN => 1,
+        WDL_CURSED_WIN => 101,
+        WDL_BLESSED_LOSS => -101,
+        WDL_LOSS => -1,
+        _ => 0,
+    };
+}
+
+fn search_dtz(board: &mut ChessBoard, state: &mut ProbeState) -> i32{
+    *state = ProbeState::Ok;
+
+    let wdl = search_wdl(board, true, state);
+
+    // draws arent stored
+    if *state == ProbeState::Fail || wdl == WDL_DRAW{
+        return 0;
+    }
+
+    if *state == ProbeState::ZeroingBestMove{
+        return get_dtz_before_zeroing(wdl);
+    }
+
+    let dtz = probe_table(board, false, wdl, state);
+
+    if *state == ProbeState::Fail{
+        return 0;
+    }
+
+    if *state != ProbeState::ChangeStm{
+        let fifty_move_plies = if wdl == WDL_BLESSED_LOSS || wdl == WDL_CURSED_WIN { 100 } else { 0 };
+
+        return (dtz + fifty_move_plies) * wdl.signum();
+    }
+
+    // the table is stored for the other side, look one move ahead
+    let mut min_dtz = 0xFFFF;
+
+    for mv in get_legal_moves(board){
+        let zeroing = is_tb_capture(board, mv) || is_pawn_move(board, mv);
+
+        let mut sub_board = board.clone();
+        make_move(&mut sub_board, mv);
+
+        // zeroing moves need the dtz before the move
+        let mut dtz: i32;
+
+        if zeroing{
+            dtz = -get_dtz_before_zeroing(search_wdl(&mut sub_board, false, state));
+        }
+        else{
+            dtz = -search_dtz(&mut sub_board, state);
+        }
+
+        if dtz == 1 && is_checkmate(&mut sub_board){
+            min_dtz = 1;
+        }
+
+        if !zeroing{
+            dtz += dtz.signum();
+        }
+
+        if dtz < min_dtz && dtz.signum() == wdl.signum(){
+            min_dtz = dtz;
+        }
+
+        if *state == ProbeState::Fail{
+            return 0;
+        }
+    }
+
+    // no legal moves, mated
+    return if min_dtz == 0xFFFF { -1 } else { min_dtz };
+}
+
+// None if the position isnt in the tablebases or the files cant be read
+pub fn probe_wdl(board: &ChessBoard) -> Option<i32>{
+    if !can_probe_tablebases(board){
+        return None;
+    }
+
+    let mut board = board.clone();
+    let mut state = ProbeState::Ok;
+
+    let wdl = search_wdl(&mut board, false, &mut state);
+
+    if state == ProbeState::Fail{
+        return None;
+    }
+
+    return Some(wdl);
+}
+
+// plies to the next capture or pawn move with best play, negative when losing and 0 for draws
+// off by one in some positions, use it to compare moves not to count down
+pub fn probe_dtz(board: &ChessBoard) -> Option<i32>{
+    if !can_probe_tablebases(board){
+        return None;
+    }
+
+    let mut board = board.clone();
+    let mut state = ProbeState::Ok;
+
+    let dtz = search_dtz(&mut board, &mut state);
+
+    if state == ProbeState::Fail{
+        return None;
+    }
+
+    return Some(dtz);
+}
+
+// ranks the root moves by dtz, higher is better
+// wins that dont run into the fifty move rule rank above TB_RANK_BOUND, the faster the higher
+// losses below -TB_RANK_BOUND, the slower the higher
+pub fn get_tb_root_move_ranks(board: &ChessBoard, moves: &[u16], game_tree: &HashMap<u64, u8>) -> Option<Vec<i32>>{
+    if !can_probe_tablebases(board){
+        return None;
+    }
+
+    let halfmove_clock = board.halfmove_clock as i32;
+
+    let mut ranks: Vec<i32> = Vec::new();
+
+    for &mv in moves{
+        let mut sub_board = board.clone();
+        make_move(&mut sub_board, mv);
+
+        let mut state = ProbeState::Ok;
+        let mut dtz: i32;
+
+        if sub_board.halfmove_clock == 0{
+            dtz = get_dtz_before_zeroing(-search_wdl(&mut sub_board, false, &mut state));
+        }
+        // would be the third time
+        else if get_position_counter(game_tree, sub_board.zobrist_hash) >= 2{
+            dtz = 0;
+        }
+        else{
+            dtz = -search_dtz(&mut sub_board, &mut state);
+            dtz += dtz.signum();
+        }
+
+        if dtz == 2 && is_checkmate(&mut sub_board){
+            dtz = 1;
+        }
+
+        if state == ProbeState::Fail{
+            return None;
+        }
+
+        let rank: i32;
+
+        if dtz > 0{
+            rank = if dtz + halfmove_clock <= 99 { MAX_DTZ - dtz } else { MAX_DTZ / 2 - (dtz + halfmove_clock) };
+        }
+        else if dtz < 0{
+            rank = if -dtz * 2 + halfmove_clock < 100 { -MAX_DTZ - dtz } else { -MAX_DTZ / 2 + (-dtz + halfmove_clock) };
+        }
+        else{
+            rank = 0;
+        }
+
+        ranks.push(rank);
+    }
+
+    return Some(ranks);
+}
+
+// the dtz a root rank came from
+pub fn get_tb_rank_dtz(rank: i32) -> i32{
+    if rank >= TB_RANK_BOUND{
+        return MAX_DTZ - rank;
+    }
+    else if rank <= -TB_RANK_BOUND{
+        return -MAX_DTZ - rank;
+    }
+
+    return 0;
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::sync::Mutex;
+
+    // the loaded tablebases are global, tests that change them cant run at the same time
+    static TB_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    fn get_test_board(fen: &str) -> ChessBoard{
+        return fen_to_GameChessBoard(fen).board;
+    }
+
+    // needs at least the 3 piece files in SYZYGY_PATH, skipped when there are none
+    #[test]
+    fn probe_known_positions(){
+        let _lock = TB_TEST_LOCK.lock().unwrap();
+
+        let path = env::var("SYZYGY_PATH").unwrap_or_default();
+        let (wdl_count, dtz_count) = init_tablebases(&path);
+
+        if wdl_count == 0{
+            println!("no tablebase files in SYZYGY_PATH, skipping");
+            return;
+        }
+
+        assert_eq!(probe_wdl(&get_test_board("8/8/8/4k3/8/8/8/3QK3 w - - 0 1")), Some(WDL_WIN));
+        assert_eq!(probe_wdl(&get_test_board("8/8/8/4k3/8/8/8/3QK3 b - - 0 1")), Some(WDL_LOSS));
+        assert_eq!(probe_wdl(&get_test_board("8/8/8/4k3/8/8/8/4K3 w - - 0 1")), Some(WDL_DRAW));
+
+        if dtz_count != 0{
+            assert!(probe_dtz(&get_test_board("8/8/8/4k3/8/8/8/3QK3 w - - 0 1")).unwrap() > 0);
+            assert!(probe_dtz(&get_test_board("8/8/8/4k3/8/8/8/3QK3 b - - 0 1")).unwrap() < 0);
+        }
+
+        init_tablebases("");
+    }
+
+    // files that pass the size and magic check but have garbage inside
+    #[test]
+    fn corrupt_files_dont_panic(){
+        let _lock = TB_TEST_LOCK.lock().unwrap();
+
+        let folder = env::temp_dir().join(format!("chess_rust_tb_test_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+
+        for fill in [0x00, 0x01, 0x5A, 0xFF]{
+            let mut bytes = vec![fill; 64 * 4 + 16];
+
+            bytes[0..4].copy_from_slice(&WDL_MAGIC);
+            // KRvK is stored for both sides
+            bytes[4] = 1;
+
+            fs::write(folder.join("KRvK.rtbw"), &bytes).unwrap();
+
+            init_tablebases(folder.to_str().unwrap());
+
+            let _ = probe_wdl(&get_test_board("8/8/8/4k3/8/8/8/3RK3 w - - 0 1"));
+            let _ = probe_wdl(&get_test_board("8/8/8/4k3/8/8/8/3RK3 b - - 0 1"));
+        }
+
+        init_tablebases("");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
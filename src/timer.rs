@@ -1,18 +1,94 @@
 use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 pub struct Timer{
     time_start: Instant,
     time_alloc: Duration,
+    // lets another thread stop the search or hold it while pondering
+    signal: Option<Arc<SearchSignal>>,
+    // nodes the search may still use, 0 means no limit
+    node_limit: u64,
 }
 
 impl Timer{
     pub fn new(duration: Duration) -> Timer{
-        return Timer{time_start: Instant::now(), time_alloc: duration}
+        return Timer{time_start: Instant::now(), time_alloc: duration, signal: None, node_limit: 0}
+    }
+
+    pub fn with_signal(duration: Duration, signal: Arc<SearchSignal>) -> Timer{
+        return Timer{time_start: Instant::now(), time_alloc: duration, signal: Some(signal), node_limit: 0}
     }
 
     pub fn time_out(&self) -> bool {
+        if let Some(signal) = &self.signal{
+            if signal.is_stopped(){
+                return true;
+            }
+
+            // no time limit until the opponent plays the expected move
+            if signal.is_pondering(){
+                return false;
+            }
+
+            // the clock only starts once pondering is over
+            return self.time_start.elapsed() > self.time_alloc + signal.get_ponderhit_time();
+        }
+
         return self.time_start.elapsed() > self.time_alloc; 
     }
+
+    pub fn get_elapsed_time(&self) -> Duration{
+        return self.time_start.elapsed();
+    }
+
+    pub fn set_node_limit(&mut self, node_limit: u64){
+        self.node_limit = node_limit;
+    }
+
+    // cheap enough to check at every node, unlike the clock
+    pub fn node_limit_reached(&self, node_counter: u32) -> bool{
+        return self.node_limit != 0 && node_counter as u64 >= self.node_limit;
+    }
+}
+
+// shared between the search and whoever is waiting on it
+pub struct SearchSignal{
+    time_start: Instant,
+    stop: AtomicBool,
+    pondering: AtomicBool,
+    // ms after time_start that the ponderhit came in
+    ponderhit_ms: AtomicU64,
+}
+
+impl SearchSignal{
+    pub fn new(pondering: bool) -> SearchSignal{
+        return SearchSignal{time_start: Instant::now(), stop: AtomicBool::new(false), pondering: AtomicBool::new(pondering), ponderhit_ms: AtomicU64::new(0)};
+    }
+
+    pub fn stop(&self){
+        self.stop.store(true, Ordering::Relaxed);
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn ponderhit(&self){
+        if self.pondering.load(Ordering::Relaxed){
+            self.ponderhit_ms.store(self.time_start.elapsed().as_millis() as u64, Ordering::Relaxed);
+            self.pondering.store(false, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_stopped(&self) -> bool{
+        return self.stop.load(Ordering::Relaxed);
+    }
+
+    pub fn is_pondering(&self) -> bool{
+        return self.pondering.load(Ordering::Relaxed);
+    }
+
+    pub fn get_ponderhit_time(&self) -> Duration{
+        return Duration::from_millis(self.ponderhit_ms.load(Ordering::Relaxed));
+    }
 }
 
 // this is literally just a profiler that I made myself 
@@ -1,46 +1,55 @@
-use std::collections::HashMap;
+use std::mem::size_of;
 use crate::functions::*;
-use crate::app_settings::TRANSPOSITION_TABLE_SIZE;
+use crate::app_settings::DEFAULT_HASH_SIZE_MB;
 
 // Entry Type:
 // 0 -> exact value
 // 1 -> Lower bound (lower than the actual value)
 // 2 -> upper bound (higher than the actual value)
 
-// type  depth
-// 0 0   0 0 0 0 0 0 
+// generation  type
+// 0 0 0 0 0 0 0 0
 
-const INFO_DEPTH_MASK : u8 = 0x3F;
+const INFO_TYPE_MASK : u8 = 0x3;
+const GENERATION_NUM : u8 = 64;
 
+// entries older than this many searches count as much as one depth less each
+const AGE_DEPTH_WEIGHT : i16 = 8;
+
+// only the top 16 bits of the hash are stored, the bucket index covers the rest
 #[derive(Clone, Copy)]
 pub struct TTEntry{
+    pub key: u16,
+    pub best_move: u16,
     pub score: i16,
+    pub depth: u8,
     pub info: u8,
-    pub best_move: u16,
-    pub hash: u64,
 }
 
 impl TTEntry{
     pub fn new(score: i16, depth: u8, entry_type: u8, best_move: u16, hash: u64) -> TTEntry{
-        // TTEntry{score:score, info:depth, visited: 0, entry_type: entry_type, best_move: best_move}
-
-        TTEntry{score: score, info: depth | (entry_type << 6), best_move: best_move, hash: hash}
-
-        // TTEntry{score: score, info: depth, visited: 0}
+        TTEntry{key: get_key(hash), best_move: best_move, score: score, depth: depth, info: entry_type}
     }
 
     pub fn null() -> TTEntry{
-        TTEntry{score: 0, info:0, best_move: 0, hash: 0}
+        TTEntry{key: 0, best_move: 0, score: 0, depth: 0, info: 0}
     }
 
     pub fn depth(&self) -> u8{
-        // return self.info;
-        return self.info & INFO_DEPTH_MASK; 
+        return self.depth; 
     }
 
     pub fn entry_type(&self) -> u8{
-        return self.info >> 6;
-        // return self.entry_type;
+        return self.info & INFO_TYPE_MASK;
+    }
+
+    pub fn generation(&self) -> u8{
+        return self.info >> 2;
+    }
+
+    // every stored search has a depth of at least 1
+    pub fn is_empty(&self) -> bool{
+        return self.depth == 0;
     }
 
     pub fn print_entry(&self){
@@ -50,14 +59,26 @@ impl TTEntry{
         info: {}
         depth: {}
         type: {}
+        generation: {}
         best move: {}
-        hash: {}
-        ",self.score, self.info, self.depth(), self.entry_type(), get_move_string(self.best_move), self.hash);
+        key: {}
+        ",self.score, self.info, self.depth(), self.entry_type(), self.generation(), get_move_string(self.best_move), self.key);
     }
 }
 
-// entry num = 4194304 (probably around 58mb)
-const TT_SIZE: usize = TRANSPOSITION_TABLE_SIZE;
+fn get_key(hash: u64) -> u16{
+    return (hash >> 48) as u16;
+}
+
+// 8 entries of 8 bytes fill up a 64 byte cache line
+pub const BUCKET_SIZE: usize = 8;
+
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+pub struct TTBucket{
+    pub entries: [TTEntry; BUCKET_SIZE],
+}
+
 
 pub const UPPER_BOUND : u8 = 2;
 pub const LOWER_BOUND : u8 = 1;
@@ -67,90 +88,169 @@ pub const REPETITION_COUNT_HASHES : [u64; 4] = [0x0, 0x278C72C79F341B64, 0x45FB1
 
 #[derive(Clone)]
 pub struct TranspositionTable{
-    pub table: Box<[TTEntry]>,
-    pub entry_num: u32,
+    pub table: Box<[TTBucket]>,
+    // the bucket number is a power of two so indexing is just a mask
+    bucket_mask: usize,
+    // increased every search so old entries can be told apart
+    pub generation: u8,
+}
+
+// largest power of two bucket number that fits in the given size
+fn get_bucket_num(size_mb: usize) -> usize{
+    let max_bucket_num = (size_mb.max(1) * 1024 * 1024) / size_of::<TTBucket>();
+
+    return 1 << (usize::BITS - 1 - max_bucket_num.leading_zeros());
 }
 
 impl TranspositionTable{
     pub fn new() -> TranspositionTable {
-        
-        let vec = vec![TTEntry::null() ; TT_SIZE];
+        return TranspositionTable::with_capacity_mb(DEFAULT_HASH_SIZE_MB);
+    }
+
+    pub fn with_capacity_mb(size_mb: usize) -> TranspositionTable {
+        let bucket_num = get_bucket_num(size_mb);
+
+        let vec = vec![TTBucket{entries: [TTEntry::null(); BUCKET_SIZE]} ; bucket_num];
 
         // Convert the Vec into a Box<[T]> (heap-allocated array)
-        
+        TranspositionTable {table: vec.into_boxed_slice(), bucket_mask: bucket_num - 1, generation: 0}
+    }
+
+    // table made out of already filled buckets (loading from a file)
+    pub fn from_buckets(buckets: Vec<TTBucket>, generation: u8) -> TranspositionTable {
+        let bucket_num = buckets.len();
 
-        TranspositionTable {table: vec.into_boxed_slice(), entry_num: 0}
+        assert!(bucket_num.is_power_of_two(), "bucket number has to be a power of two");
+
+        TranspositionTable {table: buckets.into_boxed_slice(), bucket_mask: bucket_num - 1, generation: generation % GENERATION_NUM}
     }
 
-    pub fn contains(&self, hash: u64) -> bool{
-        let table_index = hash as usize % TT_SIZE;
+    // reallocates the table, every entry is lost
+    pub fn resize(&mut self, size_mb: usize){
+        // free the old table first so both never have to fit in memory
+        self.table = Box::new([]);
 
-        if self.table[table_index].hash == hash{
-            return true;
-        }
-        else if self.table[table_index + 1].hash == hash{
-            return true
-        }
+        *self = TranspositionTable::with_capacity_mb(size_mb);
+    }
 
-        return false;
+    // bytes actually allocated for the entries
+    pub fn memory_footprint(&self) -> usize{
+        return self.table.len() * size_of::<TTBucket>();
     }
 
-    pub fn get(&self, hash: u64) -> &TTEntry{
-        let table_index = hash as usize % TT_SIZE;
+    pub fn entry_num(&self) -> usize{
+        return self.table.len() * BUCKET_SIZE;
+    }
 
-        let tt_entry = &self.table[table_index];
+    fn get_bucket_index(&self, hash: u64) -> usize{
+        return hash as usize & self.bucket_mask;
+    }
 
-        if tt_entry.hash == hash || table_index + 1 == TT_SIZE{
-            return tt_entry;
-        }
-        
-        return &self.table[table_index + 1];
+    // how many searches ago the entry was written
+    fn get_age(&self, entry: &TTEntry) -> u8{
+        return (self.generation + GENERATION_NUM - entry.generation()) % GENERATION_NUM;
     }
 
-    pub fn add(&mut self, hash:u64, score:i16, depth:u8, node_type: u8, best_move: u16){
+    pub fn new_search(&mut self){
+        self.generation = (self.generation + 1) % GENERATION_NUM;
+    }
 
-        let table_index = hash as usize % TT_SIZE;
+    pub fn contains(&self, hash: u64) -> bool{
+        return self.get(hash).is_some();
+    }
 
-        let mut tt_entry = &mut self.table[table_index];
+    pub fn get(&self, hash: u64) -> Option<TTEntry>{
+        let key = get_key(hash);
+        let bucket = &self.table[self.get_bucket_index(hash)];
 
-        // the current slot is not occupied
-        if tt_entry.hash != 0 && tt_entry.hash != hash && table_index + 1 != TT_SIZE{
-            tt_entry = &mut self.table[table_index + 1];
+        for entry in bucket.entries.iter(){
+            if entry.key == key && !entry.is_empty(){
+                return Some(*entry);
+            }
         }
-        
-        if tt_entry.hash != hash && tt_entry.hash == 0{
-            self.entry_num += 1;
+
+        return None;
+    }
+
+    pub fn add(&mut self, hash:u64, score:i16, depth:u8, node_type: u8, best_move: u16){
+        let key = get_key(hash);
+        let bucket_index = self.get_bucket_index(hash);
+
+        let mut replace_index: usize = 0;
+        let mut replace_value: i16 = i16::MAX;
+        let mut same_position = false;
+
+        for i in 0..BUCKET_SIZE{
+            let entry = &self.table[bucket_index].entries[i];
+
+            if entry.key == key && !entry.is_empty(){
+                replace_index = i;
+                same_position = true;
+                break;
+            }
+
+            // empty slots go first, then shallow and old entries
+            let value: i16;
+
+            if entry.is_empty(){
+                value = i16::MIN;
+            }
+            else{
+                value = entry.depth as i16 - AGE_DEPTH_WEIGHT * self.get_age(entry) as i16;
+            }
+
+            if value < replace_value{
+                replace_index = i;
+                replace_value = value;
+            }
         }
-        
-        if depth < tt_entry.depth() && tt_entry.hash == hash{
-            return ;
+
+        let generation = self.generation;
+        let tt_entry = &mut self.table[bucket_index].entries[replace_index];
+
+        if same_position{
+            // a deeper result from this search is worth more than a shallow bound
+            if node_type != EXACT_BOUND && depth < tt_entry.depth && tt_entry.generation() == generation{
+                return;
+            }
+
+            // dont lose the best move just because this search didnt find one
+            if best_move == 0{
+                tt_entry.score = score;
+                tt_entry.depth = depth;
+                tt_entry.info = generation << 2 | node_type;
+                return;
+            }
         }
 
+        tt_entry.key = key;
         tt_entry.score = score;
-        tt_entry.info = node_type << 6 | depth;
-        tt_entry.hash = hash;
+        tt_entry.depth = depth;
+        tt_entry.info = generation << 2 | node_type;
         tt_entry.best_move = best_move;        
     }
 
-    pub fn exceed_size(&self) -> bool{
-        return false; // trust in the process
-    }
-
-    pub fn capacity(&self) -> f32{
-        return self.entry_num as f32 / TT_SIZE as f32;
-    }
+    // permille of the sampled entries written during the current search
+    pub fn hashfull(&self) -> u16{
+        let bucket_num = (1000 / BUCKET_SIZE).min(self.table.len());
+        let mut used_entries: u16 = 0;
+
+        for bucket in self.table[0..bucket_num].iter(){
+            for entry in bucket.entries.iter(){
+                if !entry.is_empty() && entry.generation() == self.generation{
+                    used_entries += 1;
+                }
+            }
+        }
 
-    pub fn drain(&mut self){
-        // shrink factor of 4
-        // self.table.shrink_to(TT_SIZE / 4);
-        // self.table.retain(|_, k| k.visited > 2);
-        
-        // trust in the process
+        return (used_entries as usize * 1000 / (bucket_num * BUCKET_SIZE)) as u16;
     }
 
     pub fn clear(&mut self){
-        for entry in self.table.iter_mut() {
-            *entry = TTEntry::null();
+        for bucket in self.table.iter_mut() {
+            bucket.entries = [TTEntry::null(); BUCKET_SIZE];
         }
+
+        self.generation = 0;
     }
-}
\ No newline at end of file
+}
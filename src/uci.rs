@@ -0,0 +1,431 @@
+use std::io::{self, Write};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::mpsc::*;
+use std::{thread, time};
+
+use crate::app_settings::*;
+use crate::move_compute::*;
+use crate::functions::*;
+use crate::board::*;
+use crate::engine::*;
+use crate::game_board::*;
+use crate::search_tables::*;
+use crate::skill::*;
+use crate::timer::*;
+use crate::eval_params::*;
+use crate::syzygy::*;
+use crate::search_stats::*;
+
+// Universal Chess Interface
+// only the parts needed to play games through a gui
+
+// fraction of the remaining time used when the gui doesnt say how many moves are left
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+// kept back so we dont lose on time because of lag
+const MOVE_OVERHEAD: u32 = 50;
+
+// how often we look for stop / ponderhit while searching
+const INPUT_POLL_TIME: u64 = 5;
+
+// option values set by the gui
+struct UciOptions{
+    // where the search tables get saved to / loaded from
+    tables_file: String,
+    skill_level: u8,
+    contempt: i16,
+}
+
+impl UciOptions{
+    fn new() -> UciOptions{
+        UciOptions{
+            tables_file: DEFAULT_TABLES_FILE.to_string(),
+            skill_level: MAX_SKILL_LEVEL,
+            contempt: DEFAULT_CONTEMPT,
+        }
+    }
+
+    fn get_skill(&self) -> SkillLevel{
+        return SkillLevel::new(self.skill_level);
+    }
+}
+
+pub fn uci_loop(game_board: &mut GameChessBoard){
+    let mut options = UciOptions::new();
+
+    // stdin gets read on its own thread so commands can come in while searching
+    let input_rx = uci_input_reader();
+
+    // commands that came in during a search and still have to be handled
+    let mut pending_input: VecDeque<String> = VecDeque::new();
+
+    uci_identify();
+
+    loop{
+        let input_string: String;
+
+        if let Some(pending_string) = pending_input.pop_front(){
+            input_string = pending_string;
+        }
+        else if let Ok(received_string) = input_rx.recv(){
+            input_string = received_string;
+        }
+        // stdin closed
+        else{
+            break;
+        }
+
+        let tokens: Vec<String> = split(&input_string);
+
+        if tokens.len() == 0{
+            continue;
+        }
+
+        if tokens[0] == "quit"{
+            break;
+        }
+
+        else if tokens[0] == "uci"{
+            uci_identify();
+        }
+
+        else if tokens[0] == "isready"{
+            println!("readyok");
+        }
+
+        else if tokens[0] == "ucinewgame"{
+            game_board.transposition_table.lock().unwrap().clear();
+            game_board.hh_table.clear();
+        }
+
+        else if tokens[0] == "setoption"{
+            uci_set_option(game_board, &tokens, &mut options);
+        }
+
+        else if tokens[0] == "position"{
+            uci_position(game_board, &tokens);
+        }
+
+        else if tokens[0] == "go"{
+            uci_go(game_board, &tokens, &options, &input_rx, &mut pending_input);
+        }
+
+        io::stdout().flush().unwrap();
+    }
+}
+
+fn uci_input_reader() -> Receiver<String>{
+    let (tx, rx): (Sender<String>, Receiver<String>) = channel();
+
+    thread::spawn(move || {
+        loop{
+            let mut input_string = String::new();
+
+            // stdin closed, dropping tx lets the uci loop know
+            if io::stdin().read_line(&mut input_string).expect("Failed to read line") == 0{
+                break;
+            }
+
+            if tx.send(input_string).is_err(){
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+fn uci_identify(){
+    println!("id name chess_rust {}", ENGINE_VERSION);
+    println!("option name Hash type spin default {} min 1 max {}", DEFAULT_HASH_SIZE_MB, MAX_HASH_SIZE_MB);
+    println!("option name Ponder type check default false");
+    println!("option name Tables File type string default {}", DEFAULT_TABLES_FILE);
+    println!("option name Save Tables type button");
+    println!("option name Load Tables type button");
+    println!("option name Skill Level type spin default {} min 0 max {}", MAX_SKILL_LEVEL, MAX_SKILL_LEVEL);
+    println!("option name EvalFile type string default <default>");
+    println!("option name Contempt type spin default {} min {} max {}", DEFAULT_CONTEMPT, -MAX_CONTEMPT, MAX_CONTEMPT);
+    println!("option name SyzygyPath type string default <empty>");
+    println!("uciok");
+}
+
+// setoption name <id> [value <x>]
+fn uci_set_option(game_board: &mut GameChessBoard, tokens: &Vec<String>, options: &mut UciOptions){
+    let name_index = tokens.iter().position(|token| token == "name");
+    let value_index = tokens.iter().position(|token| token == "value");
+
+    let name: String;
+
+    if let Some(name_index) = name_index{
+        let name_end = value_index.unwrap_or(tokens.len());
+        name = tokens[name_index + 1..name_end].join(" ");
+    }
+    else{
+        return;
+    }
+
+    let value: String;
+
+    if let Some(value_index) = value_index{
+        value = tokens[value_index + 1..].join(" ");
+    }
+    else{
+        value = String::new();
+    }
+
+    let name = name.to_lowercase();
+
+    if name == "hash"{
+        if let Ok(size_mb) = value.parse::<usize>(){
+            game_board.transposition_table.lock().unwrap().resize(size_mb.clamp(1, MAX_HASH_SIZE_MB));
+        }
+    }
+
+    else if name == "tables file"{
+        options.tables_file = value;
+    }
+
+    else if name == "skill level"{
+        if let Ok(skill_level) = value.parse::<u8>(){
+            options.skill_level = skill_level.min(MAX_SKILL_LEVEL);
+        }
+    }
+
+    // <default> goes back to the compiled in parameters
+    else if name == "evalfile"{
+        if value == "" || value == "<default>"{
+            game_board.eval_params = EvalParams::new();
+            update_GameChessBoard_eval_params(game_board);
+            println!("info string using default eval params");
+        }
+        else{
+            match load_eval_params(&value, &mut game_board.eval_params){
+                Ok(()) => {
+                    update_GameChessBoard_eval_params(game_board);
+                    println!("info string loaded eval params from {}", value);
+                },
+                Err(error) => println!("info string could not load eval params: {}", error),
+            }
+        }
+    }
+
+    else if name == "contempt"{
+        if let Ok(contempt) = value.parse::<i16>(){
+            options.contempt = contempt.clamp(-MAX_CONTEMPT, MAX_CONTEMPT);
+        }
+    }
+
+    // folders separated by ':' (';' on windows), <empty> turns probing off
+    else if name == "syzygypath"{
+        let (wdl_count, dtz_count) = init_tablebases(&value);
+
+        println!("info string loaded {} wdl and {} dtz tablebases", wdl_count, dtz_count);
+    }
+
+    else if name == "save tables"{
+        match save_search_tables(&options.tables_file, &game_board.transposition_table.lock().unwrap(), &game_board.hh_table){
+            Ok(()) => println!("info string saved search tables to {}", options.tables_file),
+            Err(error) => println!("info string could not save search tables: {}", error),
+        }
+    }
+
+    else if name == "load tables"{
+        match load_search_tables(&options.tables_file, &mut game_board.transposition_table.lock().unwrap(), &mut game_board.hh_table){
+            Ok(()) => println!("info string loaded search tables from {}", options.tables_file),
+            Err(error) => println!("info string could not load search tables: {}", error),
+        }
+    }
+}
+
+// position [startpos | fen <fen>] [moves <move1> ... <movei>]
+fn uci_position(game_board: &mut GameChessBoard, tokens: &Vec<String>){
+    let moves_index = tokens.iter().position(|token| token == "moves").unwrap_or(tokens.len());
+
+    if tokens.len() < 2{
+        return;
+    }
+
+    if tokens[1] == "startpos"{
+        set_GameChessBoard_fen(game_board, DEFAULT_FEN);
+    }
+    else if tokens[1] == "fen"{
+        set_GameChessBoard_fen(game_board, &tokens[2..moves_index].join(" "));
+    }
+    else{
+        return;
+    }
+
+    for mv_string in tokens.iter().skip(moves_index + 1){
+        let mv = get_uci_move(&mut game_board.board, mv_string);
+
+        if mv == 0{
+            println!("info string illegal move {}", mv_string);
+            return;
+        }
+
+        game_make_move(game_board, mv);
+    }
+}
+
+// go [ponder] [wtime <x>] [btime <x>] [winc <x>] [binc <x>] [movestogo <x>] [movetime <x>] [mate <x>] [infinite]
+fn uci_go(game_board: &mut GameChessBoard, tokens: &Vec<String>, options: &UciOptions, input_rx: &Receiver<String>, pending_input: &mut VecDeque<String>){
+    let mut time_left: Option<u32> = None;
+    let mut increment: u32 = 0;
+    let mut moves_to_go: u32 = DEFAULT_MOVES_TO_GO;
+    let mut move_time: Option<u32> = None;
+    let mut mate_moves: Option<u8> = None;
+
+    let our_time = if game_board.board.board_color { "wtime" } else { "btime" };
+    let our_increment = if game_board.board.board_color { "winc" } else { "binc" };
+
+    let mut i = 1;
+
+    while i + 1 < tokens.len(){
+        let value = tokens[i + 1].parse::<u32>().ok();
+
+        if tokens[i] == our_time{
+            time_left = value;
+        }
+        else if tokens[i] == our_increment{
+            increment = value.unwrap_or(0);
+        }
+        else if tokens[i] == "movestogo"{
+            moves_to_go = value.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        }
+        else if tokens[i] == "movetime"{
+            move_time = value;
+        }
+        else if tokens[i] == "mate"{
+            mate_moves = value.map(|n| n.clamp(1, MAX_MATE_MOVES as u32) as u8);
+        }
+        else{
+            i += 1;
+            continue;
+        }
+
+        i += 2;
+    }
+
+    let mut limits: SearchLimits;
+
+    if let Some(mate_moves) = mate_moves{
+        // without a movetime it runs until the mate is proven, refuted or the gui sends stop
+        limits = SearchLimits::mate(move_time.map_or(MATE_SEARCH_TIME, |move_time| move_time.saturating_sub(MOVE_OVERHEAD).max(1)), mate_moves);
+    }
+    else{
+        let think_time: u32;
+
+        if let Some(move_time) = move_time{
+            think_time = move_time.saturating_sub(MOVE_OVERHEAD).max(1);
+        }
+        else if let Some(time_left) = time_left{
+            let usable_time = time_left.saturating_sub(MOVE_OVERHEAD);
+
+            think_time = (usable_time / moves_to_go + increment / 2).min(usable_time).max(1);
+        }
+        else{
+            think_time = DEFAULT_THINK_TIME as u32;
+        }
+
+        limits = SearchLimits::with_skill(think_time, options.get_skill());
+    }
+
+    // an infinite search waits for stop the same way pondering waits for ponderhit
+    let pondering = tokens.iter().any(|token| token == "ponder" || token == "infinite");
+
+    let signal = Arc::new(SearchSignal::new(pondering));
+
+    limits.contempt = options.contempt;
+    limits.signal = Some(signal.clone());
+    limits.uci_info = true;
+
+    let (best_move, ponder_move) = uci_search(game_board, &limits, &signal, input_rx, pending_input);
+
+    if best_move.mv == 0{
+        println!("bestmove 0000");
+    }
+    else if ponder_move == 0{
+        println!("bestmove {}", get_uci_move_string(best_move.mv));
+    }
+    else{
+        println!("bestmove {} ponder {}", get_uci_move_string(best_move.mv), get_uci_move_string(ponder_move));
+    }
+}
+
+// reports a finished depth to the gui, the pv stops at the first empty move
+pub fn print_uci_info(depth: u8, stats: &SearchStats, score: i16, nodes: u64, timer: &Timer, hashfull: u16, pv_line: &[u16]){
+    let elapsed_ms = timer.get_elapsed_time().as_millis().max(1) as u64;
+
+    let pv_string: Vec<String> = pv_line.iter().take_while(|mv| **mv != 0).map(|mv| get_uci_move_string(*mv)).collect();
+
+    println!("info depth {} seldepth {} score {} nodes {} nps {} tbhits {} time {} hashfull {} pv {}", depth, stats.seldepth.max(depth), get_score_string(score), nodes, nodes * 1000 / elapsed_ms, stats.tb_hits, elapsed_ms, hashfull, pv_string.join(" "));
+}
+
+// runs the search on another thread and listens for stop / ponderhit in the meantime
+// returns the best move and the reply we expect to it
+fn uci_search(game_board: &mut GameChessBoard, limits: &SearchLimits, signal: &SearchSignal, input_rx: &Receiver<String>, pending_input: &mut VecDeque<String>) -> (MoveScorePair, u16){
+    return thread::scope(|scope| {
+        let search_handle = scope.spawn(move || {
+            let best_move = get_limited_best_move(game_board, limits);
+
+            return (best_move, get_ponder_move(game_board, best_move.mv));
+        });
+
+        // bestmove cant be sent while we are still pondering, even if the search is done
+        while !search_handle.is_finished() || signal.is_pondering(){
+            match input_rx.recv_timeout(time::Duration::from_millis(INPUT_POLL_TIME)){
+                Ok(input_string) => {
+                    let command = input_string.trim();
+
+                    if command == "stop"{
+                        signal.stop();
+                    }
+                    else if command == "ponderhit"{
+                        signal.ponderhit();
+                    }
+                    else if command == "isready"{
+                        println!("readyok");
+                    }
+                    else if command == "quit"{
+                        signal.stop();
+                        pending_input.push_back(input_string);
+                    }
+                    else if command != ""{
+                        pending_input.push_back(input_string);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // nobody is left to send stop or ponderhit
+                Err(RecvTimeoutError::Disconnected) => {
+                    if signal.is_pondering(){
+                        signal.stop();
+                    }
+
+                    thread::sleep(time::Duration::from_millis(INPUT_POLL_TIME));
+                }
+            }
+
+            io::stdout().flush().unwrap();
+        }
+
+        return search_handle.join().unwrap();
+    });
+}
+
+// finds the legal move matching the uci string, 0 if there is none
+pub fn get_uci_move(chess_board: &mut ChessBoard, mv_string: &str) -> u16{
+    let mut move_buffer = MoveBuffer::new();
+
+    get_moves(chess_board, &mut move_buffer);
+
+    for i in 0..move_buffer.index{
+        let mv = move_buffer.mv_arr[i];
+
+        if get_uci_move_string(mv) == mv_string{
+            return mv;
+        }
+    }
+
+    return 0;
+}
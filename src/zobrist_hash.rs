@@ -844,4 +844,22 @@ pub fn get_full_zobrist_hash(chess_board: &ChessBoard) -> u64{
     }
 
     return zobrist_hash;
-}
\ No newline at end of file
+}
+// only the pawns, used to look up the pawn structure in the pawn hash table
+pub fn get_full_pawn_hash(chess_board: &ChessBoard) -> u64{
+    let mut pawn_hash : u64 = 0;
+
+    for i in [0, 6]{
+        let mut temp_bitboard = chess_board.piece_bitboards[i as usize];
+
+        while temp_bitboard != 0{
+            let square : u8 = temp_bitboard.trailing_zeros() as u8;
+
+            pawn_hash ^= zobrist_hash_table[get_zobrist_piece_index(i, square)];
+
+            temp_bitboard ^= 1 << square;
+        }
+    }
+
+    return pawn_hash;
+}